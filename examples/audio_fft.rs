@@ -2,10 +2,12 @@
 // use std::io::BufReader;
 // use rodio::{Decoder, OutputStream, source::Source};
 use std::fs::File;
+use std::io;
 use std::path::Path;
+use fft_rust::FftError;
 
 
-fn main() {
+fn main() -> Result<(), FftError> {
     // // Get a output stream handle to the default physical sound device
     // let (_stream, stream_handle) = OutputStream::try_default().unwrap();
     // // Load a sound from a file, using a path relative to Cargo.toml
@@ -22,11 +24,14 @@ fn main() {
     // // so we need to keep the main thread alive while it's playing.
     // std::thread::sleep(std::time::Duration::from_secs(30));
 
-    let mut inp_file = File::open(Path::new("audio/song.wav")).unwrap();
-    let (header, data) = wav::read(&mut inp_file).unwrap();
+    let mut inp_file = File::open(Path::new("audio/song.wav"))?;
+    let (header, data) = wav::read(&mut inp_file)?;
     println!("header -> {:?}", header);
-    let song_bytes = data.as_sixteen().unwrap();
+    let song_bytes = data.as_sixteen().ok_or_else(
+        || io::Error::new(io::ErrorKind::InvalidData, "expected 16-bit samples")
+    )?;
     println!("song_bytes -> {:?}", song_bytes[441000..441100].iter().step_by(2).map(
         |x| x
     ).collect::<Vec<_>>());
+    Ok(())
 }
\ No newline at end of file
@@ -91,21 +91,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
 
 
-    let y = combined_signal.iter().map(|x| *x as f64).collect();
-    let mut y_complex = fft_rust::float_array_to_complex(y);
-    let mut y_fft = fft_rust::fft(&mut y_complex);
-    // println!("delta length between original signal and the fft: {:?}", (y_fft.len() - length));
-
-    let y_fft_real = fft_rust::complex_array_to_float(&mut y_fft);
-    let y_fft_real_scale: Vec<_> = y_fft_real.iter().map(
-        |x| *x/(length as f64)
-    ).collect();
-    let p1_temp: Vec<f64> = y_fft_real_scale[..=(length/2 + 1)].iter().map(|x| *x).collect();
-    let p1: Vec<f64>  = p1_temp[..].iter().enumerate().map(
-        |(i, p)| if i == 0 {*p} else if i == p1_temp.len() - 1 {*p} else {2.0*p}
-    ).collect();
-    let f: Vec<f32> = (0..(length/2)).map(|i| (i as f32)*fs/(length as f32)).collect();
-    let fft_graph = f.iter().zip(p1.iter());
+    // the transforms are generic over f32 and f64, so the f32 samples plotters
+    // wants can go straight through the fft; the one-sided amplitude spectrum
+    // shows each sinusoid at its amplitude
+    let spectrum = fft_rust::amplitude_spectrum(&combined_signal, fs);
+    let fft_graph = spectrum.frequencies.iter().zip(spectrum.magnitudes.iter());
 
     let mut cc = ChartBuilder::on(
         &child_drawing_areas[2]
@@ -123,7 +113,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .draw()?;
 
     cc.draw_series(LineSeries::new(
-        fft_graph.map(|(x, y)| ((*x as f32), (*y as f32))),
+        fft_graph.map(|(x, y)| (*x, *y)),
         &BLACK,
     ))?
         .label("FFT")
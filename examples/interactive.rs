@@ -178,26 +178,11 @@ fn build_plot_widget() -> impl Widget<State> {
             .draw()
             .unwrap();
 
-        let mut y_complex = fft_rust::float_array_to_complex(y_sig);
-        let mut y_fft = fft_rust::fft(&mut y_complex);
-
-        let y_fft_real = fft_rust::complex_array_to_float(&mut y_fft);
-        let y_fft_real_scale: Vec<_> = y_fft_real.iter().map(
-            |x| *x/(length as f64)
-        ).collect();
-        let p1_temp: Vec<f64> = y_fft_real_scale[..=(length/2 + 1)].iter().map(
-            |x| *x
-        ).collect();
-        let p1: Vec<f64>  = p1_temp[..].iter().enumerate().map(
-            |(i, p)| if i == 0 {*p} else if i == p1_temp.len() - 1 {*p} else {2.0*p}
-        ).collect();
-        let f: Vec<f32> = (0..(length/2)).map(
-            |i| (i as f32)*(fs as f32)/(length as f32)
-        ).collect();
-        let fft = f.iter().map(
-            |f| *f
+        let spectrum = fft_rust::amplitude_spectrum(&y_sig, fs as f64);
+        let fft = spectrum.frequencies.iter().map(
+            |f| *f as f32
         ).zip(
-            p1.iter().map(
+            spectrum.magnitudes.iter().map(
                 |p| *p as f32
             )
         );
@@ -0,0 +1,40 @@
+use std::f64::consts::PI;
+use fft_rust::{Colormap, RenderOptions, StftConfig, Window};
+
+
+const OUT_FILE_NAME: &str = "plotters-doc-data/spectrogram.png";
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // two seconds at 8 kHz: a chirp sweeping from 100 Hz to 3 kHz over a steady 1 kHz tone
+    let fs = 8000.0;
+    let length = 16000;
+    let (f_start, f_end, duration) = (100.0, 3000.0, 2.0);
+    let signal: Vec<f64> = (0..length).map(|n| {
+        let t = n as f64 / fs;
+        // the phase of a linear chirp is the integral of its frequency
+        let chirp = (2.0 * PI * (f_start * t + (f_end - f_start) * t * t / (2.0 * duration))).sin();
+        let tone = 0.3 * (2.0 * PI * 1000.0 * t).sin();
+        chirp + tone
+    }).collect();
+
+    // 512-sample Hann frames every 128 samples: 64 ms of resolution, COLA for resynthesis
+    let config = StftConfig::new(Window::Hann, 512, 128);
+    config.check_cola()?;
+    let spectrogram = fft_rust::spectrogram(&signal, fs, &config);
+
+    fft_rust::render_spectrogram(
+        &spectrogram.to_decibels(80.0),
+        OUT_FILE_NAME,
+        &RenderOptions {
+            colormap: Colormap::Magma,
+            title: "Chirp from 100 Hz to 3 kHz over a 1 kHz tone".to_string(),
+            ..RenderOptions::default()
+        },
+    )?;
+    println!("Result has been saved to {}", OUT_FILE_NAME);
+    Ok(())
+}
+
+#[test]
+fn entry_point() {
+    main().unwrap()
+}
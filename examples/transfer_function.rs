@@ -0,0 +1,62 @@
+use std::env;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use fft_rust::{Detrend, Estimator, FftError, Window};
+
+
+/// the first channel of a 16-bit or 32-bit float WAV file, and its sample rate
+fn read_channel(path: &str) -> Result<(f64, Vec<f64>), FftError> {
+    let mut inp_file = File::open(Path::new(path))?;
+    let (header, data) = wav::read(&mut inp_file)?;
+    let channels = header.channel_count as usize;
+    let samples: Vec<f64> = if let Some(samples) = data.as_sixteen() {
+        samples.iter().step_by(channels).map(|x| *x as f64 / i16::MAX as f64).collect()
+    } else if let Some(samples) = data.as_thirty_two_float() {
+        samples.iter().step_by(channels).map(|x| *x as f64).collect()
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData, "expected 16-bit or 32-bit float samples"
+        ).into());
+    };
+    Ok((header.sampling_rate as f64, samples))
+}
+
+fn main() -> Result<(), FftError> {
+    // the stimulus played into the system and the response recorded from it
+    let args: Vec<String> = env::args().collect();
+    let stimulus_path = args.get(1).map_or("audio/stimulus.wav", |s| s.as_str());
+    let response_path = args.get(2).map_or("audio/response.wav", |s| s.as_str());
+    let (fs, stimulus) = read_channel(stimulus_path)?;
+    let (_, mut response) = read_channel(response_path)?;
+    response.resize(stimulus.len(), 0.0);
+
+    // 4096-sample Hann segments with 50% overlap
+    let (segment_len, overlap) = (4096, 2048);
+    let (frequencies, h1) = fft_rust::tfestimate(
+        &stimulus, &response, fs, segment_len, overlap, Window::Hann, Detrend::Constant, Estimator::H1
+    );
+    let (_, coherence) = fft_rust::coherence(
+        &stimulus, &response, fs, segment_len, overlap, Window::Hann, Detrend::Constant
+    );
+
+    // one row per third of an octave from 20 Hz up to Nyquist, skipping
+    // repeats where the bins are further apart than that
+    println!("{:>10} {:>10} {:>10} {:>10}", "freq (Hz)", "gain (dB)", "phase (°)", "coherence");
+    let step = frequencies[1];
+    let mut f = 20.0;
+    let mut last = usize::MAX;
+    while f < fs / 2.0 {
+        let k = (f / step).round() as usize;
+        f *= 2f64.powf(1.0 / 3.0);
+        if k == last {
+            continue;
+        }
+        last = k;
+        println!(
+            "{:>10.1} {:>10.2} {:>10.1} {:>10.3}",
+            frequencies[k], 20.0 * h1[k].norm().log10(), h1[k].arg().to_degrees(), coherence[k]
+        );
+    }
+    Ok(())
+}
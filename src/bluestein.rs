@@ -0,0 +1,120 @@
+//! Bluestein's chirp-z algorithm
+//!
+//! a length-N DFT can be rewritten as a convolution using
+//! nk = (n^2 + k^2 - (k - n)^2) / 2, so
+//! X_k = w_k * sum_n (x_n * w_n) * conj(w_(k-n)) with w_k = e^(-i * pi * k^2 / N).
+//! the convolution is done circularly with power-of-two ffts of length
+//! M >= 2N - 1, which gives exact length-N transforms for any N.
+
+use std::f64::consts::PI;
+use num::complex::Complex;
+
+use crate::float::{zero, FftFloat};
+use crate::plan::{Algorithm, Direction, FftPlan};
+
+#[derive(Debug, Clone)]
+pub(crate) struct Bluestein<T: FftFloat> {
+    len: usize,
+    inverse: bool,
+    // w_k for k in 0..len
+    chirp: Vec<Complex<T>>,
+    // fft of conj(w) laid out circularly over the padded length
+    kernel: Vec<Complex<T>>,
+    forward: FftPlan<T>,
+    backward: FftPlan<T>,
+    buffer: Vec<Complex<T>>,
+}
+
+impl<T: FftFloat> Bluestein<T> {
+    pub(crate) fn new(len: usize, direction: Direction, algorithm: Algorithm) -> Bluestein<T> {
+        let inverse = direction == Direction::Inverse;
+        let padded_len = crate::next_power_of_two(2 * len - 1);
+        let chirp = chirp(len, inverse);
+
+        let mut forward = FftPlan::with_algorithm(padded_len, Direction::Forward, algorithm);
+        let backward = FftPlan::with_algorithm(padded_len, Direction::Inverse, algorithm);
+
+        let mut kernel = vec![zero(); padded_len];
+        kernel[0] = chirp[0].conj();
+        for k in 1..len {
+            kernel[k] = chirp[k].conj();
+            kernel[padded_len - k] = chirp[k].conj();
+        }
+        forward.process(&mut kernel);
+
+        Bluestein {
+            len,
+            inverse,
+            chirp,
+            kernel,
+            forward,
+            backward,
+            buffer: vec![zero(); padded_len],
+        }
+    }
+
+    pub(crate) fn process(&mut self, data: &mut [Complex<T>]) {
+        for (i, b) in self.buffer.iter_mut().enumerate() {
+            *b = if i < self.len {
+                data[i] * self.chirp[i]
+            } else {
+                zero()
+            };
+        }
+
+        // the inverse plan divides by the padded length, which is exactly the
+        // scaling a circular convolution through the fft needs
+        self.forward.process(&mut self.buffer);
+        for (b, k) in self.buffer.iter_mut().zip(self.kernel.iter()) {
+            *b *= k;
+        }
+        self.backward.process(&mut self.buffer);
+
+        let scale = if self.inverse { T::from_usize(self.len) } else { T::one() };
+        for (i, x) in data.iter_mut().enumerate() {
+            *x = self.buffer[i] * self.chirp[i] / scale;
+        }
+    }
+}
+
+/// e^(sign * i * pi * k^2 / len) for k in 0..len
+fn chirp<T: FftFloat>(len: usize, inverse: bool) -> Vec<Complex<T>> {
+    let sign = if inverse { 1f64 } else { -1f64 };
+    (0..len).map(|k| {
+        // k^2 mod 2N keeps the angle small so large k doesn't lose precision
+        let k_squared = ((k as u128 * k as u128) % (2 * len as u128)) as f64;
+        let w = Complex::new(0.0, sign * PI * k_squared / (len as f64)).exp();
+        Complex::new(T::from_f64(w.re), T::from_f64(w.im))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{dft, signal};
+
+    #[test]
+    fn bluestein_matches_dft_test() {
+        for len in [1, 3, 5, 6, 7, 12, 100, 127] {
+            let input = signal(len, 1);
+            for algorithm in [Algorithm::Recursive, Algorithm::Iterative] {
+                let mut output = input.clone();
+                Bluestein::<f64>::new(len, Direction::Forward, algorithm).process(&mut output);
+                for (a, b) in output.iter().zip(dft(&input, false).iter()) {
+                    assert!((a - b).norm() < 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bluestein_inverse_test() {
+        let input: Vec<_> = (0..10).map(|x| Complex::new(x as f64, -(x as f64))).collect();
+        let mut output = input.clone();
+        Bluestein::new(10, Direction::Forward, Algorithm::Recursive).process(&mut output);
+        Bluestein::new(10, Direction::Inverse, Algorithm::Recursive).process(&mut output);
+        for (a, b) in output.iter().zip(input.iter()) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+}
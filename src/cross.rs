@@ -0,0 +1,235 @@
+//! cross-spectral estimates between two signals
+//!
+//! both signals are cut into the same segments as `welch_psd` and the
+//! products of their spectra averaged. the cross spectrum S_xy averages
+//! conj(X) Y, so if y is x through a linear system H, S_xy = H S_xx. that
+//! gives the transfer function from input x to output y, and the coherence
+//! says how much of y is explained by x at each frequency.
+
+use num::complex::Complex;
+
+use crate::float::FftFloat;
+use crate::psd::{Detrend, PsdScaling, Segments};
+use crate::window::Window;
+
+/// which estimate of the transfer function `tfestimate` returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Estimator {
+    /// S_xy / S_xx; unbiased when the noise is on the output
+    #[default]
+    H1,
+    /// S_yy / S_yx; unbiased when the noise is on the input
+    H2,
+}
+
+/// the auto and cross spectra of two signals, from the same segments
+struct CrossSpectra<T: FftFloat> {
+    frequencies: Vec<T>,
+    xx: Vec<T>,
+    yy: Vec<T>,
+    xy: Vec<Complex<T>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cross_spectra<T: FftFloat>(
+    x: &[T], y: &[T], fs: T, segment_len: usize, overlap: usize, window: Window,
+    detrend: Detrend, scaling: PsdScaling
+) -> CrossSpectra<T> {
+    assert_eq!(
+        x.len(), y.len(),
+        "the signals have {} and {} samples; they need to be the same length", x.len(), y.len()
+    );
+    let segments = Segments::new(x.len(), segment_len, overlap, window);
+    let (x_spectra, y_spectra) = (segments.spectra(x, detrend), segments.spectra(y, detrend));
+    let scale = segments.scale(fs, scaling);
+    let bins = segments.bins;
+    CrossSpectra {
+        frequencies: segments.frequencies(fs),
+        xx: segments.average(|k| column(&x_spectra, k, bins).map(|x| x.norm_sqr()).sum::<T>() * scale),
+        yy: segments.average(|k| column(&y_spectra, k, bins).map(|y| y.norm_sqr()).sum::<T>() * scale),
+        xy: segments.average(|k| {
+            let products = column(&x_spectra, k, bins).zip(column(&y_spectra, k, bins));
+            products.map(|(x, y)| x.conj() * y).sum::<Complex<T>>() * scale
+        }),
+    }
+}
+
+/// bin `k` of every segment's spectrum, from rows of `bins`
+fn column<T: FftFloat>(
+    spectra: &[Complex<T>], k: usize, bins: usize
+) -> impl Iterator<Item = &Complex<T>> {
+    spectra.iter().skip(k).step_by(bins)
+}
+
+/// estimate the one-sided cross spectral density of `x` and `y`, sampled at
+/// `fs`, with Welch's method, like `scipy.signal.csd`; returns the
+/// frequencies and the average of conj(X) Y at each
+///
+/// `csd(x, x, ...)` is `welch_psd(x, ...)`; see `welch_psd` for the other
+/// arguments and when it panics. also panics if `x` and `y` differ in length
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{Detrend, PsdScaling, Window};
+///
+/// // y is x a quarter cycle later, so the cross spectrum's phase is -pi/2
+/// let x: Vec<f64> = (0..1024).map(|n| (2.0 * std::f64::consts::PI * n as f64 / 16.0).cos()).collect();
+/// let y: Vec<f64> = (0..1024).map(|n| (2.0 * std::f64::consts::PI * n as f64 / 16.0).sin()).collect();
+/// let (_, density) = fft_rust::csd(
+///     &x, &y, 16., 128, 64, Window::Hann, Detrend::Constant, PsdScaling::Spectrum
+/// );
+/// assert!((density[8].norm() - 0.5).abs() < 1e-9);
+/// assert!((density[8].arg() + std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn csd<T: FftFloat>(
+    x: &[T], y: &[T], fs: T, segment_len: usize, overlap: usize, window: Window,
+    detrend: Detrend, scaling: PsdScaling
+) -> (Vec<T>, Vec<Complex<T>>) {
+    let spectra = cross_spectra(x, y, fs, segment_len, overlap, window, detrend, scaling);
+    (spectra.frequencies, spectra.xy)
+}
+
+/// the magnitude-squared coherence of `x` and `y`, |S_xy|^2 / (S_xx S_yy),
+/// like `scipy.signal.coherence`: 1 where y is entirely x through a linear
+/// system, 0 where they're unrelated, NaN where either has no power
+///
+/// it takes several segments to mean anything; a single segment is always
+/// perfectly coherent. panics in the same cases as `csd`
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{Detrend, Window};
+///
+/// // y is a scaled, delayed copy of x
+/// let x: Vec<f64> = (0..4096).map(|n| ((n * 7919) % 1009) as f64 / 1009.0 - 0.5).collect();
+/// let y: Vec<f64> = (0..4096).map(|n| if n < 2 { 0.0 } else { 3.0 * x[n - 2] }).collect();
+/// let (_, coherence) = fft_rust::coherence(&x, &y, 1., 256, 128, Window::Hann, Detrend::Constant);
+/// assert!(coherence[1..].iter().all(|c| *c > 0.95));
+/// ```
+pub fn coherence<T: FftFloat>(
+    x: &[T], y: &[T], fs: T, segment_len: usize, overlap: usize, window: Window, detrend: Detrend
+) -> (Vec<T>, Vec<T>) {
+    let spectra = cross_spectra(x, y, fs, segment_len, overlap, window, detrend, PsdScaling::Density);
+    let coherence = spectra.xy.iter().zip(spectra.xx.iter().zip(spectra.yy.iter())).map(
+        |(xy, (xx, yy))| xy.norm_sqr() / (*xx * *yy)
+    ).collect();
+    (spectra.frequencies, coherence)
+}
+
+/// estimate the frequency response of the system that turns input `x` into
+/// output `y`, like MATLAB's `tfestimate`; returns the frequencies and the
+/// complex response at each (its norm is the gain, its arg the phase)
+///
+/// H1 suits a clean stimulus and a noisy measured response, H2 a noisy
+/// stimulus; where the coherence is high they agree. panics in the same
+/// cases as `csd`
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{Detrend, Estimator, Window};
+///
+/// // y[n] = 0.5 x[n] + 0.5 x[n - 1], a two-tap moving average: unity gain
+/// // at DC, falling to nothing at Nyquist
+/// let x: Vec<f64> = (0..4096).map(|n| ((n * 7919) % 1009) as f64 / 1009.0 - 0.5).collect();
+/// let y: Vec<f64> = (0..4096).map(|n| 0.5 * x[n] + if n > 0 { 0.5 * x[n - 1] } else { 0.0 }).collect();
+/// let (frequencies, response) = fft_rust::tfestimate(
+///     &x, &y, 1., 256, 128, Window::Hann, Detrend::None, Estimator::H1
+/// );
+/// assert!((response[0].norm() - 1.0).abs() < 0.01);
+/// assert!(response[128].norm() < 0.01);
+/// assert_eq!(frequencies[128], 0.5);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn tfestimate<T: FftFloat>(
+    x: &[T], y: &[T], fs: T, segment_len: usize, overlap: usize, window: Window,
+    detrend: Detrend, estimator: Estimator
+) -> (Vec<T>, Vec<Complex<T>>) {
+    let spectra = cross_spectra(x, y, fs, segment_len, overlap, window, detrend, PsdScaling::Density);
+    let response = match estimator {
+        Estimator::H1 => spectra.xy.iter().zip(spectra.xx.iter()).map(|(xy, xx)| xy / *xx).collect(),
+        Estimator::H2 => spectra.xy.iter().zip(spectra.yy.iter()).map(
+            |(xy, yy)| Complex::new(*yy, T::zero()) / xy.conj()
+        ).collect(),
+    };
+    (spectra.frequencies, response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::noise;
+    use std::f64::consts::PI;
+
+    /// y = h * x, the first samples taking x as zero before it starts
+    fn filter(x: &[f64], h: &[f64]) -> Vec<f64> {
+        (0..x.len()).map(|n| {
+            h.iter().enumerate().filter(|(k, _)| *k <= n).map(|(k, h)| h * x[n - k]).sum()
+        }).collect()
+    }
+
+    /// the frequency response of the FIR filter `h` at `f` cycles per sample
+    fn response(h: &[f64], f: f64) -> Complex<f64> {
+        h.iter().enumerate().map(|(k, h)| Complex::new(0.0, -2.0 * PI * f * k as f64).exp() * h).sum()
+    }
+
+    #[test]
+    fn csd_matches_welch_test() {
+        let x = noise(2000, 11);
+        let (frequencies, auto) = csd(
+            &x, &x, 10.0, 200, 100, Window::Hann, Detrend::Linear, PsdScaling::Density
+        );
+        let (expected_frequencies, expected) = crate::welch_psd(
+            &x, 10.0, 200, 100, Window::Hann, Detrend::Linear, PsdScaling::Density
+        );
+        assert_eq!(frequencies, expected_frequencies);
+        for (a, b) in auto.iter().zip(expected.iter()) {
+            assert!((a.re - b).abs() < 1e-12 && a.im == 0.0);
+        }
+    }
+
+    #[test]
+    fn tfestimate_test() {
+        // a three-tap filter with a delay in it, measured with white noise
+        let h = [0.0, 0.5, 0.3, 0.2];
+        let x = noise(50_000, 5);
+        let y = filter(&x, &h);
+        for estimator in [Estimator::H1, Estimator::H2] {
+            let (frequencies, estimate) = tfestimate(
+                &x, &y, 1.0, 512, 256, Window::Hann, Detrend::None, estimator
+            );
+            for (f, estimate) in frequencies.iter().zip(estimate.iter()) {
+                assert!((estimate - response(&h, *f)).norm() < 0.01, "{:?} differs at {}", estimator, f);
+            }
+        }
+        let (_, coherence) = coherence(&x, &y, 1.0, 512, 256, Window::Hann, Detrend::None);
+        assert!(coherence.iter().all(|c| *c > 0.999));
+    }
+
+    #[test]
+    fn output_noise_test() {
+        // noise on the output leaves H1 alone, inflates H2 and lowers the
+        // coherence to |H|^2 S_xx / (|H|^2 S_xx + S_nn) = 0.5 for this gain
+        let x = noise(100_000, 8);
+        let disturbance = noise(100_000, 9);
+        let y: Vec<f64> = x.iter().zip(disturbance.iter()).map(|(x, n)| 2.0 * x + 2.0 * n).collect();
+        let mean = |v: &[f64]| v[1..v.len() - 1].iter().sum::<f64>() / (v.len() - 2) as f64;
+        let gain = |estimator| {
+            let (_, h) = tfestimate(&x, &y, 1.0, 256, 128, Window::Hann, Detrend::None, estimator);
+            mean(&h.iter().map(|h| h.norm()).collect::<Vec<_>>())
+        };
+        assert!((gain(Estimator::H1) - 2.0).abs() < 0.05);
+        assert!((gain(Estimator::H2) - 4.0).abs() < 0.2);
+        let (_, coherence) = coherence(&x, &y, 1.0, 256, 128, Window::Hann, Detrend::None);
+        assert!((mean(&coherence) - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    #[should_panic]
+    fn csd_rejects_mismatched_lengths_test() {
+        csd(&[1f64; 16], &[1f64; 15], 1.0, 8, 4, Window::Hann, Detrend::None, PsdScaling::Density);
+    }
+}
@@ -0,0 +1,92 @@
+//! the crate's error type
+
+use std::fmt;
+use std::io;
+
+/// everything the fallible (`try_`) functions can fail with
+#[derive(Debug)]
+pub enum FftError {
+    /// the input had no samples
+    EmptyInput,
+    /// a padded or combined length doesn't fit in a `usize`
+    LengthOverflow,
+    /// two lengths that have to agree (two inputs, or an input and a plan) don't
+    MismatchedShapes { expected: usize, found: usize },
+    /// the input has a NaN or infinite value at `index`
+    NonFiniteInput { index: usize },
+    /// an stft window's square doesn't overlap-add to a constant at its hop;
+    /// `ripple` is how far the sum strays, relative to its peak
+    NotCola { ripple: f64 },
+    /// an stft's hop is zero, so its frames never advance
+    ZeroHop,
+    /// drawing a chart failed
+    Render(String),
+    /// reading or writing a file failed
+    Io(io::Error),
+}
+
+impl fmt::Display for FftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FftError::EmptyInput => write!(f, "input is empty"),
+            FftError::LengthOverflow => write!(f, "transform length overflows usize"),
+            FftError::MismatchedShapes { expected, found } => write!(
+                f, "expected {} samples, found {}", expected, found
+            ),
+            FftError::NonFiniteInput { index } => write!(
+                f, "input sample {} is NaN or infinite", index
+            ),
+            FftError::NotCola { ripple } => write!(
+                f, "window is not COLA at this hop: overlap-added gain ripples by {:.3e}", ripple
+            ),
+            FftError::ZeroHop => write!(f, "an stft needs a hop of at least one sample"),
+            FftError::Render(err) => write!(f, "rendering failed: {}", err),
+            FftError::Io(err) => write!(f, "i/o error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for FftError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FftError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FftError {
+    fn from(err: io::Error) -> FftError {
+        FftError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        assert_eq!(FftError::EmptyInput.to_string(), "input is empty");
+        assert_eq!(
+            FftError::MismatchedShapes { expected: 4, found: 3 }.to_string(),
+            "expected 4 samples, found 3"
+        );
+        assert_eq!(
+            FftError::NonFiniteInput { index: 2 }.to_string(),
+            "input sample 2 is NaN or infinite"
+        );
+        assert_eq!(
+            FftError::NotCola { ripple: 0.5 }.to_string(),
+            "window is not COLA at this hop: overlap-added gain ripples by 5.000e-1"
+        );
+        assert_eq!(FftError::ZeroHop.to_string(), "an stft needs a hop of at least one sample");
+    }
+
+    #[test]
+    fn from_io_error_test() {
+        let err: FftError = io::Error::new(io::ErrorKind::NotFound, "song.wav").into();
+        assert!(matches!(err, FftError::Io(_)));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
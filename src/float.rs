@@ -0,0 +1,81 @@
+//! the floating point types the transforms run on
+
+use std::f64::consts::PI;
+use std::fmt::Debug;
+use std::iter::Sum;
+use num::complex::Complex;
+use num::traits::{Float, FloatConst, NumAssign};
+
+/// a float type the transforms can be run on; implemented for `f32` and `f64`
+///
+/// twiddle factors and butterfly constants are always computed in `f64` and
+/// then rounded, so single precision transforms only lose accuracy in the
+/// arithmetic itself
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let arg = &mut vec![Complex::new(2f32, 0f32), Complex::new(2f32, 0f32)];
+/// assert_eq!(fft_rust::fft(arg), vec![Complex::new(4f32, 0f32), Complex::new(0f32, 0f32)]);
+/// ```
+pub trait FftFloat:
+    Float + FloatConst + NumAssign + Sum + Default + Debug + Send + Sync + 'static
+{
+    /// convert an `f64`, rounding to the nearest representable value
+    fn from_f64(x: f64) -> Self;
+
+    /// convert a length or an index
+    fn from_usize(n: usize) -> Self;
+}
+
+impl FftFloat for f32 {
+    fn from_f64(x: f64) -> f32 {
+        x as f32
+    }
+
+    fn from_usize(n: usize) -> f32 {
+        n as f32
+    }
+}
+
+impl FftFloat for f64 {
+    fn from_f64(x: f64) -> f64 {
+        x
+    }
+
+    fn from_usize(n: usize) -> f64 {
+        n as f64
+    }
+}
+
+/// e^(sign * 2i * pi * k / n), computed in f64
+pub(crate) fn twiddle<T: FftFloat>(k: usize, n: usize, sign: f64) -> Complex<T> {
+    let w = Complex::new(0.0, sign * 2.0 * PI * (k as f64)/(n as f64)).exp();
+    Complex::new(T::from_f64(w.re), T::from_f64(w.im))
+}
+
+/// complex zero
+pub(crate) fn zero<T: FftFloat>() -> Complex<T> {
+    Complex::new(T::zero(), T::zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn twiddle_test() {
+        let w: Complex<f64> = twiddle(1, 4, -1f64);
+        assert!((w - Complex::new(0f64, -1f64)).norm() < 1e-15);
+        let w: Complex<f32> = twiddle(1, 4, 1f64);
+        assert!((w - Complex::new(0f32, 1f32)).norm() < 1e-7);
+    }
+
+    #[test]
+    fn from_usize_test() {
+        assert_eq!(f32::from_usize(3), 3f32);
+        assert_eq!(f64::from_usize(1024), 1024f64);
+    }
+}
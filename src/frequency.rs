@@ -0,0 +1,199 @@
+//! frequency bins and spectrum reordering
+//!
+//! an fft's output starts at DC, climbs to the highest positive frequency,
+//! then wraps around to the most negative frequency and climbs back towards
+//! zero. `fftfreq` and `rfftfreq` say which frequency each bin is, and
+//! `fftshift` moves DC to the middle so the spectrum reads left to right
+//! from negative to positive frequencies.
+
+use crate::float::FftFloat;
+
+/// the frequency of each bin of a length-`n` fft of samples `d` apart (so
+/// 1/d is the sample rate), in the order `fft_exact` returns them:
+/// 0, 1, ..., then the negative frequencies, all divided by n * d
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(fft_rust::fftfreq(4, 0.5), vec![0., 0.5, -1., -0.5]);
+/// assert_eq!(fft_rust::fftfreq(5, 1.), vec![0., 0.2, 0.4, -0.4, -0.2]);
+/// ```
+pub fn fftfreq<T: FftFloat>(n: usize, d: T) -> Vec<T> {
+    let scale = T::from_usize(n) * d;
+    (0..n).map(|k| {
+        if k < n.div_ceil(2) {
+            T::from_usize(k) / scale
+        } else {
+            -T::from_usize(n - k) / scale
+        }
+    }).collect()
+}
+
+/// the frequency of each of the n/2 + 1 bins `rfft` returns for a length-`n`
+/// signal of samples `d` apart: 0, 1, ..., n/2, all divided by n * d
+///
+/// # Example
+///
+/// ```
+/// // 8 samples at 1 kHz
+/// assert_eq!(fft_rust::rfftfreq(8, 1e-3), vec![0., 125., 250., 375., 500.]);
+/// ```
+pub fn rfftfreq<T: FftFloat>(n: usize, d: T) -> Vec<T> {
+    let scale = T::from_usize(n) * d;
+    (0..=n / 2).map(|k| T::from_usize(k) / scale).collect()
+}
+
+/// move the zero-frequency bin of a spectrum to the middle (index n/2), so
+/// negative frequencies come first; works on anything, e.g. complex bins or
+/// magnitudes
+///
+/// # Example
+///
+/// ```
+/// let bins = &mut fft_rust::fftfreq(5, 1.);
+/// fft_rust::fftshift(bins);
+/// assert_eq!(bins, &[-0.4, -0.2, 0., 0.2, 0.4]);
+/// ```
+pub fn fftshift<T>(data: &mut [T]) {
+    let half = data.len() / 2;
+    data.rotate_right(half);
+}
+
+/// undo `fftshift`, moving the middle bin back to index 0; the two only
+/// differ for odd lengths
+///
+/// # Example
+///
+/// ```
+/// let bins = &mut vec![-0.4, -0.2, 0., 0.2, 0.4];
+/// fft_rust::ifftshift(bins);
+/// assert_eq!(bins, &fft_rust::fftfreq(5, 1.));
+/// ```
+pub fn ifftshift<T>(data: &mut [T]) {
+    let half = data.len() / 2;
+    data.rotate_left(half);
+}
+
+/// `fftshift` along each of `axes` of a row-major buffer with the given
+/// `shape` (see `fftn`); panics if an axis is out of range or `data.len()`
+/// isn't the product of `shape`
+///
+/// # Example
+///
+/// ```
+/// // a 2 x 3 spectrum; DC moves from the top left to row 1, column 1
+/// let data = &mut [
+///     1, 2, 3,
+///     4, 5, 6,
+/// ];
+/// fft_rust::fftshiftn(data, &[2, 3], &[0, 1]);
+/// assert_eq!(data, &[
+///     6, 4, 5,
+///     3, 1, 2,
+/// ]);
+/// ```
+pub fn fftshiftn<T>(data: &mut [T], shape: &[usize], axes: &[usize]) {
+    shift_n(data, shape, axes, false)
+}
+
+/// undo `fftshiftn` along each of `axes`
+///
+/// # Example
+///
+/// ```
+/// let data = &mut [
+///     6, 4, 5,
+///     3, 1, 2,
+/// ];
+/// fft_rust::ifftshiftn(data, &[2, 3], &[0, 1]);
+/// assert_eq!(data, &[
+///     1, 2, 3,
+///     4, 5, 6,
+/// ]);
+/// ```
+pub fn ifftshiftn<T>(data: &mut [T], shape: &[usize], axes: &[usize]) {
+    shift_n(data, shape, axes, true)
+}
+
+fn shift_n<T>(data: &mut [T], shape: &[usize], axes: &[usize], inverse: bool) {
+    let size = shape.iter().try_fold(1usize, |size, len| size.checked_mul(*len));
+    assert_eq!(
+        Some(data.len()), size,
+        "expected a {:?} buffer, found {} samples", shape, data.len()
+    );
+    for axis in axes {
+        assert!(*axis < shape.len(), "axis {} is out of range for shape {:?}", axis, shape);
+    }
+    if data.is_empty() {
+        return;
+    }
+
+    for axis in axes {
+        // a block of len * stride samples is `len` slices along the axis, so
+        // rotating the block by whole slices shifts along the axis
+        let stride: usize = shape[axis + 1..].iter().product();
+        let block = shape[*axis] * stride;
+        let by = shape[*axis] / 2 * stride;
+        for block in data.chunks_exact_mut(block) {
+            if inverse {
+                block.rotate_left(by);
+            } else {
+                block.rotate_right(by);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fftfreq_test() {
+        assert_eq!(fftfreq(1, 1f64), vec![0.]);
+        let bins = fftfreq(6, 0.1f64);
+        for (bin, k) in bins.iter().zip([0., 1., 2., -3., -2., -1.]) {
+            assert!((bin - k / 0.6).abs() < 1e-12);
+        }
+        assert_eq!(fftfreq(8, 1f32)[4], -0.5);
+        assert_eq!(rfftfreq(1, 1f64), vec![0.]);
+        assert_eq!(rfftfreq(7, 1f64).len(), 4);
+        assert_eq!(*rfftfreq(2048, 1f32 / 1024.).last().unwrap(), 512.);
+    }
+
+    #[test]
+    fn shift_test() {
+        for len in 0..8 {
+            let input: Vec<_> = (0..len).collect();
+            let mut data = input.clone();
+            fftshift(&mut data);
+            if len > 0 {
+                assert_eq!(data[len / 2], 0);
+            }
+            ifftshift(&mut data);
+            assert_eq!(data, input);
+        }
+    }
+
+    #[test]
+    fn shift_n_test() {
+        // shifting every axis of a 3-D buffer puts the origin at the centre
+        let shape = [3, 4, 5];
+        let mut data: Vec<_> = (0..60).collect();
+        fftshiftn(&mut data, &shape, &[0, 1, 2]);
+        assert_eq!(data[5 * 4 + 2 * 5 + 2], 0);
+        ifftshiftn(&mut data, &shape, &[2, 0, 1]);
+        assert_eq!(data, (0..60).collect::<Vec<_>>());
+
+        // one axis of a 2-D buffer is a 1-D shift of each column
+        let mut data: Vec<_> = (0..6).collect();
+        fftshiftn(&mut data, &[3, 2], &[0]);
+        assert_eq!(data, vec![4, 5, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn shift_n_rejects_wrong_size_test() {
+        fftshiftn(&mut [0; 5], &[2, 2], &[0]);
+    }
+}
@@ -2,8 +2,45 @@
 //!
 //! A library for the Cooley-Tukey Fast Fourier Transform (fft) algorithm.
 
-use std::f64::consts::PI;
-use num::complex::{Complex, ComplexFloat};
+use num::complex::Complex;
+
+mod bluestein;
+mod cross;
+mod error;
+mod float;
+mod frequency;
+mod lomb;
+mod mixed_radix;
+mod multidim;
+mod multitaper;
+mod parallel;
+mod plan;
+mod psd;
+mod rader;
+mod real;
+mod simd;
+mod spectrogram;
+mod spectrum;
+mod stft;
+mod window;
+
+pub use cross::{coherence, csd, tfestimate, Estimator};
+pub use error::FftError;
+pub use float::FftFloat;
+pub use frequency::{fftfreq, fftshift, fftshiftn, ifftshift, ifftshiftn, rfftfreq};
+pub use lomb::{lomb_scargle, LombScargle};
+pub use multidim::{fft2, fftn, ifft2, ifftn};
+pub use multitaper::{dpss, multitaper_psd, Multitaper, TaperWeighting};
+pub use parallel::Parallelism;
+pub use plan::{Algorithm, Direction, FftPlan, Normalization};
+pub use psd::{periodogram, welch_psd, Detrend, PsdScaling};
+pub use real::{irfft, rfft};
+pub use spectrogram::{render_spectrogram, spectrogram, Colormap, RenderOptions, Spectrogram};
+pub use spectrum::{
+    amplitude_spectrum, amplitude_spectrum_windowed, power_spectrum, power_spectrum_windowed, Spectrum,
+};
+pub use stft::{istft, stft, try_istft, Stft, StftConfig};
+pub use window::{window, window_stats, Symmetry, Window, WindowStats};
 
 /// given a list of a polynomial's coefficients (or any array of numbers),
 /// compute the FFT using the Cooley-Tukey algorithm; return the resulting array
@@ -34,32 +71,57 @@ use num::complex::{Complex, ComplexFloat};
 ///     Complex::new(-5f64, 0f64), Complex::new(9f64, 2f64)
 /// ]);
 /// ```
-pub fn fft(p: &mut Vec<Complex<f64>>) -> Vec<Complex<f64>> {
-    // Given an array of coefficients, p,
-    // Recursively perform a Cooley-Tukey Fast Fourier Transform
-
-    // if p is length 1, then just return p
-    let mut n = p.len();
-    if n == 1 {
-        return p.iter().map(|x| *x).collect::<Vec<Complex<f64>>>();
-    }
-
-    // ensure length of p is a power of two
-    if !is_int_power_of_two(n) {
-        zero_pad_left(p, next_power_of_two(n) - p.len());
-        n = p.len();
-    }
+pub fn fft<T: FftFloat>(p: &mut Vec<Complex<T>>) -> Vec<Complex<T>> {
+    cooley_tukey(p, false, Parallelism::default())
+}
 
-    // even_powered_coeffs
-    let y_e = fft(
-        &mut p[..].iter().step_by(2).map(|x| *x).collect::<Vec<_>>()
-    );
-    // odd_powered_coeffs
-    let y_o = fft(
-        &mut p[1..].iter().step_by(2).map(|x| *x).collect::<Vec<_>>()
-    );
+/// like `fft`, but with an explicit thread budget; the top levels of the
+/// recursion are split across up to `parallelism.threads` threads, and the
+/// result is bit-identical to the single-threaded one
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::Parallelism;
+///
+/// let arg = &mut vec![Complex::new(2f64, 0f64), Complex::new(2f64, 0f64)];
+/// let parallelism = Parallelism { threads: 2, min_len: 2 };
+/// assert_eq!(fft_rust::fft_parallel(arg, parallelism), vec![
+///     Complex::new(4f64, 0f64), Complex::new(0f64, 0f64)
+/// ]);
+/// ```
+pub fn fft_parallel<T: FftFloat>(
+    p: &mut Vec<Complex<T>>, parallelism: Parallelism
+) -> Vec<Complex<T>> {
+    cooley_tukey(p, false, parallelism)
+}
 
-    butterfly(n, y_e, y_o, false)
+/// fallible version of `fft`: returns an error instead of panicking or
+/// misbehaving on empty input, NaN/infinite samples, or a padded length that
+/// overflows; the input itself is left untouched
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::FftError;
+///
+/// let arg = &[Complex::new(2f64, 0f64), Complex::new(2f64, 0f64)];
+/// assert_eq!(fft_rust::try_fft(arg).unwrap(), vec![
+///     Complex::new(4f64, 0f64), Complex::new(0f64, 0f64)
+/// ]);
+///
+/// let empty: &[Complex<f64>] = &[];
+/// assert!(matches!(fft_rust::try_fft(empty), Err(FftError::EmptyInput)));
+///
+/// let arg = &[Complex::new(2f64, 0f64), Complex::new(f64::NAN, 0f64)];
+/// assert!(matches!(fft_rust::try_fft(arg), Err(FftError::NonFiniteInput { index: 1 })));
+/// ```
+pub fn try_fft<T: FftFloat>(p: &[Complex<T>]) -> Result<Vec<Complex<T>>, FftError> {
+    check_input(p)?;
+    try_next_power_of_two(p.len())?;
+    Ok(fft(&mut p.to_vec()))
 }
 
 /// given a list an array of complex numbers, compute the Inverse-FFT
@@ -94,14 +156,41 @@ pub fn fft(p: &mut Vec<Complex<f64>>) -> Vec<Complex<f64>> {
 ///     Complex::new(-5f64, 0f64), Complex::new(1f64, 0f64)
 /// ]);
 /// ```
-pub fn ifft(p: &mut Vec<Complex<f64>>) -> Vec<Complex<f64>> {
+pub fn ifft<T: FftFloat>(p: &mut Vec<Complex<T>>) -> Vec<Complex<T>> {
+    cooley_tukey(p, true, Parallelism::default())
+}
+
+/// like `ifft`, but with an explicit thread budget (see `fft_parallel`)
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::Parallelism;
+///
+/// let arg = &mut vec![Complex::new(4f64, 0f64), Complex::new(0f64, 0f64)];
+/// assert_eq!(fft_rust::ifft_parallel(arg, Parallelism::serial()), vec![
+///     Complex::new(2f64, 0f64), Complex::new(2f64, 0f64)
+/// ]);
+/// ```
+pub fn ifft_parallel<T: FftFloat>(
+    p: &mut Vec<Complex<T>>, parallelism: Parallelism
+) -> Vec<Complex<T>> {
+    cooley_tukey(p, true, parallelism)
+}
+
+/// the recursion behind `fft` and `ifft`; sub-transforms of at least
+/// `parallelism.min_len` samples hand their odd half to another thread
+fn cooley_tukey<T: FftFloat>(
+    p: &mut Vec<Complex<T>>, inverse: bool, parallelism: Parallelism
+) -> Vec<Complex<T>> {
     // Given an array of complex numbers, p,
-    // Recursively perform an Inverse Cooley-Tukey Fast Fourier Transform
+    // Recursively perform a (possibly Inverse) Cooley-Tukey Fast Fourier Transform
 
     // if p is length 1, then just return p
     let mut n = p.len();
     if n == 1 {
-        return p.iter().map(|x| *x).collect::<Vec<Complex<f64>>>();
+        return p.to_vec();
     }
 
     // ensure length of p is a power of two
@@ -110,53 +199,314 @@ pub fn ifft(p: &mut Vec<Complex<f64>>) -> Vec<Complex<f64>> {
         n = p.len();
     }
 
-    // even_powered_coeffs
-    let y_e = ifft(
-        &mut p[..].iter().step_by(2).map(|x| *x).collect::<Vec<_>>()
+    // even_powered_coeffs and odd_powered_coeffs, on two threads if p is long enough
+    let (even, odd) = parallelism.halves();
+    let (y_e, y_o) = parallel::join(
+        parallelism.splits(n),
+        || cooley_tukey(
+            &mut p[..].iter().step_by(2).copied().collect::<Vec<_>>(), inverse, even
+        ),
+        || cooley_tukey(
+            &mut p[1..].iter().step_by(2).copied().collect::<Vec<_>>(), inverse, odd
+        ),
     );
-    // odd_powered_coeffs
-    let y_o = ifft(
-        &mut p[1..].iter().step_by(2).map(|x| *x).collect::<Vec<_>>()
+
+    butterfly(n, y_e, y_o, inverse)
+}
+
+/// fallible version of `ifft`; see `try_fft` for what is checked
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let arg = &[Complex::new(4f64, 0f64), Complex::new(0f64, 0f64)];
+/// assert_eq!(fft_rust::try_ifft(arg).unwrap(), vec![
+///     Complex::new(2f64, 0f64), Complex::new(2f64, 0f64)
+/// ]);
+/// ```
+pub fn try_ifft<T: FftFloat>(p: &[Complex<T>]) -> Result<Vec<Complex<T>>, FftError> {
+    check_input(p)?;
+    try_next_power_of_two(p.len())?;
+    Ok(ifft(&mut p.to_vec()))
+}
+
+/// check that `p` is non-empty and every sample is finite
+pub(crate) fn check_input<T: FftFloat>(p: &[Complex<T>]) -> Result<(), FftError> {
+    if p.is_empty() {
+        return Err(FftError::EmptyInput);
+    }
+    match p.iter().position(|x| !x.re.is_finite() || !x.im.is_finite()) {
+        Some(index) => Err(FftError::NonFiniteInput { index }),
+        None => Ok(()),
+    }
+}
+
+/// compute the length-N DFT of `p` for any N, without padding
+///
+/// `fft` pads non-power-of-two input up to the next power of two, which
+/// changes the bins; here bin k always corresponds to frequency k * fs / N.
+/// non-power-of-two lengths are handled by the mixed-radix, Rader and
+/// Bluestein kernels (see `FftPlan`)
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let arg = &[
+///     Complex::new(2f64, 0f64), Complex::new(7f64, 0f64),
+///     Complex::new(-1f64, 0f64)
+/// ];
+/// let answer = &mut fft_rust::fft_exact(arg);
+/// assert_eq!(answer.len(), 3);
+/// assert_eq!(fft_rust::approximate_complex(answer)[0], Complex::new(8f64, 0f64));
+/// ```
+pub fn fft_exact<T: FftFloat>(p: &[Complex<T>]) -> Vec<Complex<T>> {
+    let mut output = p.to_vec();
+    FftPlan::new(p.len(), Direction::Forward).process(&mut output);
+    output
+}
+
+/// compute the length-N Inverse-DFT of `p` for any N, without padding
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let arg = &[
+///     Complex::new(2f64, 0f64), Complex::new(7f64, 0f64),
+///     Complex::new(-1f64, 0f64)
+/// ];
+/// let answer = &mut fft_rust::ifft_exact(&fft_rust::fft_exact(arg));
+/// assert_eq!(fft_rust::approximate_complex(answer), &mut arg.to_vec());
+/// ```
+pub fn ifft_exact<T: FftFloat>(p: &[Complex<T>]) -> Vec<Complex<T>> {
+    let mut output = p.to_vec();
+    FftPlan::new(p.len(), Direction::Inverse).process(&mut output);
+    output
+}
+
+/// compute the length-`len` DFT (no padding) of `count` signals stored back
+/// to back in `data`, in place, reusing one plan for all of them; large
+/// batches are spread across threads the same way `fft` is (see `Parallelism`)
+///
+/// panics if `data.len()` isn't `len * count`
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// // two channels of length 2
+/// let data = &mut [
+///     Complex::new(2f64, 0f64), Complex::new(2f64, 0f64),
+///     Complex::new(1f64, 0f64), Complex::new(-1f64, 0f64)
+/// ];
+/// fft_rust::fft_batch(data, 2, 2);
+/// assert_eq!(data, &[
+///     Complex::new(4f64, 0f64), Complex::new(0f64, 0f64),
+///     Complex::new(0f64, 0f64), Complex::new(2f64, 0f64)
+/// ]);
+/// ```
+pub fn fft_batch<T: FftFloat>(data: &mut [Complex<T>], len: usize, count: usize) {
+    batch(data, len, count, Direction::Forward)
+}
+
+/// compute the length-`len` Inverse-DFT of `count` back-to-back signals in
+/// place (see `fft_batch`)
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let data = &mut [
+///     Complex::new(4f64, 0f64), Complex::new(0f64, 0f64),
+///     Complex::new(0f64, 0f64), Complex::new(2f64, 0f64)
+/// ];
+/// fft_rust::ifft_batch(data, 2, 2);
+/// assert_eq!(data, &[
+///     Complex::new(2f64, 0f64), Complex::new(2f64, 0f64),
+///     Complex::new(1f64, 0f64), Complex::new(-1f64, 0f64)
+/// ]);
+/// ```
+pub fn ifft_batch<T: FftFloat>(data: &mut [Complex<T>], len: usize, count: usize) {
+    batch(data, len, count, Direction::Inverse)
+}
+
+fn batch<T: FftFloat>(data: &mut [Complex<T>], len: usize, count: usize, direction: Direction) {
+    assert_eq!(
+        Some(data.len()), len.checked_mul(count),
+        "expected {} signals of length {}, found {} samples", count, len, data.len()
     );
+    if count == 0 {
+        return;
+    }
+    let mut plan = FftPlan::new(len, direction);
+    plan.set_parallelism(Parallelism::default());
+    plan.process_batch(data);
+}
+
+/// compute the length-`len` DFT (no padding) of `data[offset]`,
+/// `data[offset + stride]`, ... in place, leaving the other samples alone;
+/// see `FftPlan::process_strided`
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// // the right channel of interleaved stereo
+/// let data = &mut [
+///     Complex::new(1f64, 0f64), Complex::new(5f64, 0f64),
+///     Complex::new(3f64, 0f64), Complex::new(5f64, 0f64)
+/// ];
+/// fft_rust::fft_strided(data, 1, 2, 2);
+/// assert_eq!(data, &[
+///     Complex::new(1f64, 0f64), Complex::new(10f64, 0f64),
+///     Complex::new(3f64, 0f64), Complex::new(0f64, 0f64)
+/// ]);
+/// ```
+pub fn fft_strided<T: FftFloat>(
+    data: &mut [Complex<T>], offset: usize, stride: usize, len: usize
+) {
+    FftPlan::new(len, Direction::Forward).process_strided(data, offset, stride)
+}
+
+/// compute the length-`len` Inverse-DFT of a strided signal in place (see `fft_strided`)
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let data = &mut [
+///     Complex::new(1f64, 0f64), Complex::new(10f64, 0f64),
+///     Complex::new(3f64, 0f64), Complex::new(0f64, 0f64)
+/// ];
+/// fft_rust::ifft_strided(data, 1, 2, 2);
+/// assert_eq!(data[1], Complex::new(5f64, 0f64));
+/// assert_eq!(data[3], Complex::new(5f64, 0f64));
+/// ```
+pub fn ifft_strided<T: FftFloat>(
+    data: &mut [Complex<T>], offset: usize, stride: usize, len: usize
+) {
+    FftPlan::new(len, Direction::Inverse).process_strided(data, offset, stride)
+}
+
+/// compute the length-N DFT of `p` (no padding) scaled by the given convention
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::Normalization;
+///
+/// let arg = &[Complex::new(2f64, 0f64), Complex::new(2f64, 0f64)];
+/// assert_eq!(fft_rust::fft_normalized(arg, Normalization::Forward), vec![
+///     Complex::new(2f64, 0f64), Complex::new(0f64, 0f64)
+/// ]);
+/// assert_eq!(fft_rust::fft_normalized(arg, Normalization::Backward), vec![
+///     Complex::new(4f64, 0f64), Complex::new(0f64, 0f64)
+/// ]);
+/// ```
+pub fn fft_normalized<T: FftFloat>(
+    p: &[Complex<T>], normalization: Normalization
+) -> Vec<Complex<T>> {
+    let mut output = p.to_vec();
+    let mut plan = FftPlan::new(p.len(), Direction::Forward);
+    plan.set_normalization(normalization);
+    plan.process(&mut output);
+    output
+}
+
+/// compute the length-N Inverse-DFT of `p` (no padding) scaled by the given
+/// convention; pass the same `normalization` used for the forward transform
+/// to get the original signal back
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::Normalization;
+///
+/// let arg = &[Complex::new(2f64, 0f64), Complex::new(0f64, 0f64)];
+/// assert_eq!(fft_rust::ifft_normalized(arg, Normalization::Forward), vec![
+///     Complex::new(2f64, 0f64), Complex::new(2f64, 0f64)
+/// ]);
+/// ```
+pub fn ifft_normalized<T: FftFloat>(
+    p: &[Complex<T>], normalization: Normalization
+) -> Vec<Complex<T>> {
+    let mut output = p.to_vec();
+    let mut plan = FftPlan::new(p.len(), Direction::Inverse);
+    plan.set_normalization(normalization);
+    plan.process(&mut output);
+    output
+}
+
+/// compute the FFT of a power-of-two length slice in place using the
+/// iterative (bit-reversal) Cooley-Tukey algorithm
+///
+/// unlike `fft` this never pads or allocates per level, so it panics if the
+/// length of `p` is not a power of two
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let arg = &mut [
+///     Complex::new(4f64, 0f64), Complex::new(3f64, 0f64),
+///     Complex::new(-5f64, 0f64), Complex::new(1f64, 0f64)
+/// ];
+/// fft_rust::fft_in_place(arg);
+/// assert_eq!(arg, &[
+///     Complex::new(3f64, 0f64), Complex::new(9f64, -2f64),
+///     Complex::new(-5f64, 0f64), Complex::new(9f64, 2f64)
+/// ]);
+/// ```
+pub fn fft_in_place<T: FftFloat>(p: &mut [Complex<T>]) {
+    FftPlan::with_algorithm(p.len(), Direction::Forward, Algorithm::Iterative).process(p)
+}
 
-    butterfly(n, y_e, y_o, true)
+/// compute the Inverse-FFT of a power-of-two length slice in place using the
+/// iterative (bit-reversal) Cooley-Tukey algorithm
+///
+/// panics if the length of `p` is not a power of two
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let arg = &mut [Complex::new(-2f64, 0f64), Complex::new(4f64, 0f64)];
+/// fft_rust::ifft_in_place(arg);
+/// assert_eq!(arg, &[Complex::new(1f64, 0f64), Complex::new(-3f64, 0f64)]);
+/// ```
+pub fn ifft_in_place<T: FftFloat>(p: &mut [Complex<T>]) {
+    FftPlan::with_algorithm(p.len(), Direction::Inverse, Algorithm::Iterative).process(p)
 }
 
 /// given two lists (one of even and one of odd coefficient outputs)
 /// and boolean, inverse, run the butterfly algorithm; return the resulting array
-fn butterfly(
-    n: usize, y_e: Vec<Complex<f64>>, y_o: Vec<Complex<f64>>, inverse: bool
-) -> Vec<Complex<f64>> {
+fn butterfly<T: FftFloat>(
+    n: usize, mut y_e: Vec<Complex<T>>, mut y_o: Vec<Complex<T>>, inverse: bool
+) -> Vec<Complex<T>> {
     let sign = (if inverse {1} else {-1}) as f64;
 
-    // create an array of e^(2i * pi) multiplied by k, (0 to n-1), and divided by N
-    let omega = (0..n).map(
-        |k| Complex::new(0.0, sign * 2.0 * PI * (k as f64)/(n as f64)).exp()
-    ).collect::<Vec<Complex<f64>>>();
-
-    let n_over_two = n as i32 / 2;
-    let mut y = vec![Complex::new(0.0, 0.0); n];
+    // create an array of e^(2i * pi) multiplied by k, (0 to n/2 - 1), and divided by N
+    let omega = (0..n / 2).map(
+        |k| float::twiddle(k, n, sign)
+    ).collect::<Vec<Complex<T>>>();
 
-    if inverse {
-        for i in 0..n_over_two {
-            let idx_a = i as usize;
-            let idx_b = n_over_two as usize + idx_a;
-
-            let temp = omega[idx_a] * y_o[idx_a];
-            y[idx_a] = (y_e[idx_a] + temp) / 2f64;
-            y[idx_b] = (y_e[idx_a] - temp) / 2f64;
-        }
-    } else {
-        for i in 0..n_over_two {
-            let idx_a = i as usize;
-            let idx_b = n_over_two as usize + idx_a;
-
-            let temp = omega[idx_a] * y_o[idx_a];
-            y[idx_a] = y_e[idx_a] + temp;
-            y[idx_b] = y_e[idx_a] - temp;
-        }
-    }
-    y
+    // y_e becomes the lower half of the result and y_o the upper half
+    simd::radix2(&mut y_e, &mut y_o, &omega, 1, inverse);
+    y_e.append(&mut y_o);
+    y_e
 }
 
 /// round complex numbers that are really close to an integer
@@ -171,7 +521,7 @@ fn butterfly(
 ///     Complex::new(1f64, 0f64), Complex::new(2f64, 0f64)
 /// ]);
 /// ```
-pub fn approximate_complex(p :&mut Vec<Complex<f64>>) -> &mut Vec<Complex<f64>>{
+pub fn approximate_complex<T: FftFloat>(p :&mut Vec<Complex<T>>) -> &mut Vec<Complex<T>>{
     for mut complex_num in &mut *p {
         complex_num.re = complex_num.re.round();
         complex_num.im = complex_num.im.round();
@@ -180,52 +530,203 @@ pub fn approximate_complex(p :&mut Vec<Complex<f64>>) -> &mut Vec<Complex<f64>>{
 }
 
 
-/// do convolution/multiplication, i.e. fft -> multiply point-wise -> ifft
+/// which part of a linear convolution or correlation to return, with the
+/// same meaning as NumPy's `mode` argument (N and M are the input lengths)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvolveMode {
+    /// every point where the inputs overlap at all; N + M - 1 samples
+    Full,
+    /// the middle max(N, M) samples of `Full`
+    Same,
+    /// only points where the inputs overlap completely; max(N, M) - min(N, M) + 1 samples
+    Valid,
+}
+
+/// linear convolution, i.e. zero pad both lists to at least N + M - 1 ->
+/// fft -> multiply point-wise -> ifft, then keep the part `mode` asks for
+///
+/// convolving two lists of coefficients multiplies the polynomials they
+/// describe (or, for lists of digits, the numbers without the carries)
 ///
 /// # Example
 ///
 /// ```
 /// use num::complex::Complex;
+/// use fft_rust::ConvolveMode;
 ///
-/// let list_a = &mut vec![  // binary 0110 -> decimal 6
-///     Complex::new(0f64, 0f64), Complex::new(1f64, 0f64),
-///     Complex::new(1f64, 0f64), Complex::new(0f64, 0f64)
-/// ];
-/// let list_b = &mut vec![  // binary 0010 -> decimal 2
-///     Complex::new(0f64, 0f64), Complex::new(0f64, 0f64),
-///     Complex::new(1f64, 0f64), Complex::new(0f64, 0f64)
-/// ];
-/// let output1 = &mut fft_rust::convolve(list_a, list_b);
-/// assert_eq!(fft_rust::approximate_complex(output1), &mut vec![  // binary 1100 -> decimal 12
-///     Complex::new(1f64, 0f64), Complex::new(1f64, 0f64),
-///     Complex::new(0f64, 0f64), Complex::new(0f64, 0f64)
-/// ]);
+/// let list_a = &fft_rust::float_array_to_complex(vec![0f64, 1., 1., 0.]);  // binary 0110 -> decimal 6
+/// let list_b = &fft_rust::float_array_to_complex(vec![0f64, 0., 1., 0.]);  // binary 0010 -> decimal 2
+/// let output1 = &mut fft_rust::convolve(list_a, list_b, ConvolveMode::Full);
+/// assert_eq!(
+///     fft_rust::complex_array_to_float(fft_rust::approximate_complex(output1)),
+///     vec![0., 0., 0., 1., 1., 0., 0.]  // binary 0001100 -> decimal 12
+/// );
 ///
-/// let list_c = &mut vec![  // decimal 25
-///     Complex::new(0f64, 0f64), Complex::new(0f64, 0f64),
-///     Complex::new(2f64, 0f64), Complex::new(5f64, 0f64)
-/// ];
-/// let list_d = &mut vec![  // decimal 25
-///     Complex::new(0f64, 0f64), Complex::new(0f64, 0f64),
-///     Complex::new(2f64, 0f64), Complex::new(5f64, 0f64)
-/// ];
-/// list_c.reverse();  // why do i need to reverse this one to get the answer??
-/// let output2 = &mut fft_rust::convolve(list_c, list_d);
-/// assert_eq!(fft_rust::approximate_complex(output2), &mut vec![  // decimal 400 + 200 + 25 = 625
-///     Complex::new(0f64, 0f64), Complex::new(4f64, 0f64),
-///     Complex::new(20f64, 0f64), Complex::new(25f64, 0f64)
-/// ]);
+/// let list_c = &fft_rust::float_array_to_complex(vec![0f64, 0., 2., 5.]);  // decimal 25
+/// let output2 = &mut fft_rust::convolve(list_c, list_c, ConvolveMode::Full);
+/// assert_eq!(
+///     fft_rust::complex_array_to_float(fft_rust::approximate_complex(output2)),
+///     vec![0., 0., 0., 0., 4., 20., 25.]  // decimal 400 + 200 + 25 = 625
+/// );
+///
+/// let signal = &fft_rust::float_array_to_complex(vec![1f64, 2., 3.]);
+/// let kernel = &fft_rust::float_array_to_complex(vec![0f64, 1., 0.5]);
+/// let output3 = &mut fft_rust::convolve(signal, kernel, ConvolveMode::Same);
+/// assert_eq!(
+///     fft_rust::complex_array_to_float(output3).iter().map(|x| (x * 10.).round() / 10.).collect::<Vec<_>>(),
+///     vec![1., 2.5, 4.]
+/// );
+/// ```
+pub fn convolve<T: FftFloat>(
+    list1: &[Complex<T>], list2: &[Complex<T>], mode: ConvolveMode
+) -> Vec<Complex<T>> {
+    if list1.is_empty() || list2.is_empty() {
+        return vec![];
+    }
+    let full_len = list1.len() + list2.len() - 1;
+    let fft_len = next_power_of_two(full_len);
+    let mut fft_list1 = pad(list1, fft_len, Padding::ZeroRight);
+    let mut fft_list2 = pad(list2, fft_len, Padding::ZeroRight);
+    FftPlan::new(fft_len, Direction::Forward).process(&mut fft_list1);
+    FftPlan::new(fft_len, Direction::Forward).process(&mut fft_list2);
+
+    let mut conv_output = multiply_pointwise(&mut fft_list1, &mut fft_list2);
+    FftPlan::new(fft_len, Direction::Inverse).process(&mut conv_output);
+    conv_output.truncate(full_len);
+    trim_to_mode(conv_output, list1.len(), list2.len(), mode)
+}
+
+/// fallible version of `convolve`: both inputs must be non-empty and finite,
+/// and N + M - 1 (padded to a power of two) must fit in a usize
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{ConvolveMode, FftError};
+///
+/// let list_a = &fft_rust::float_array_to_complex(vec![1f64, 2.]);
+/// let output = &mut fft_rust::try_convolve(list_a, list_a, ConvolveMode::Full).unwrap();
+/// assert_eq!(
+///     fft_rust::complex_array_to_float(fft_rust::approximate_complex(output)),
+///     vec![1., 4., 4.]
+/// );
+/// assert!(matches!(
+///     fft_rust::try_convolve(list_a, &[], ConvolveMode::Full), Err(FftError::EmptyInput)
+/// ));
 /// ```
-pub fn convolve(
-    list1 :&mut Vec<Complex<f64>>, list2 :&mut Vec<Complex<f64>>
-) -> Vec<Complex<f64>> {
-    let fft_list1 = &mut fft(list1);
-    let fft_list2 = &mut fft(list2);
-    let conv_output = &mut multiply_pointwise(fft_list1, fft_list2);
-    ifft(conv_output)
+pub fn try_convolve<T: FftFloat>(
+    list1: &[Complex<T>], list2: &[Complex<T>], mode: ConvolveMode
+) -> Result<Vec<Complex<T>>, FftError> {
+    check_input(list1)?;
+    check_input(list2)?;
+    let full_len = (list1.len() - 1).checked_add(list2.len()).ok_or(FftError::LengthOverflow)?;
+    try_next_power_of_two(full_len)?;
+    Ok(convolve(list1, list2, mode))
 }
 
-/// point-wise multiplication
+/// linear cross-correlation, c_k = sum_n list1[n + k] * conj(list2[n]), with
+/// the same output modes as `convolve` (NumPy's `correlate`); in `Full` mode
+/// the lags run from -(M - 1) up to N - 1
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::ConvolveMode;
+///
+/// let signal = &fft_rust::float_array_to_complex(vec![0f64, 0., 1., 2., 0.]);
+/// let pattern = &fft_rust::float_array_to_complex(vec![1f64, 2.]);
+/// let output = &mut fft_rust::correlate(signal, pattern, ConvolveMode::Valid);
+/// assert_eq!(
+///     fft_rust::complex_array_to_float(fft_rust::approximate_complex(output)),
+///     vec![0., 2., 5., 2.]  // best match at lag 2
+/// );
+/// ```
+pub fn correlate<T: FftFloat>(
+    list1: &[Complex<T>], list2: &[Complex<T>], mode: ConvolveMode
+) -> Vec<Complex<T>> {
+    let reversed: Vec<_> = list2.iter().rev().map(|x| x.conj()).collect();
+    convolve(list1, &reversed, mode)
+}
+
+/// circular convolution of two lists; the shorter one is zero padded on the
+/// right to the length of the longer one and the result wraps around at
+/// that length
+///
+/// # Example
+///
+/// ```
+/// let list_a = &fft_rust::float_array_to_complex(vec![1f64, 2., 3.]);
+/// let list_b = &fft_rust::float_array_to_complex(vec![0f64, 1.]);
+/// let output = &mut fft_rust::convolve_circular(list_a, list_b);
+/// assert_eq!(
+///     fft_rust::complex_array_to_float(fft_rust::approximate_complex(output)),
+///     vec![3., 1., 2.]
+/// );
+/// ```
+pub fn convolve_circular<T: FftFloat>(
+    list1: &[Complex<T>], list2: &[Complex<T>]
+) -> Vec<Complex<T>> {
+    circular(list1, list2, false)
+}
+
+/// circular cross-correlation, c_k = sum_n list1[(n + k) mod N] * conj(list2[n]),
+/// with the shorter list zero padded on the right to the longer one's length
+///
+/// # Example
+///
+/// ```
+/// let list_a = &fft_rust::float_array_to_complex(vec![1f64, 2., 3.]);
+/// let list_b = &fft_rust::float_array_to_complex(vec![0f64, 1.]);
+/// let output = &mut fft_rust::correlate_circular(list_a, list_b);
+/// assert_eq!(
+///     fft_rust::complex_array_to_float(fft_rust::approximate_complex(output)),
+///     vec![2., 3., 1.]
+/// );
+/// ```
+pub fn correlate_circular<T: FftFloat>(
+    list1: &[Complex<T>], list2: &[Complex<T>]
+) -> Vec<Complex<T>> {
+    circular(list1, list2, true)
+}
+
+fn circular<T: FftFloat>(
+    list1: &[Complex<T>], list2: &[Complex<T>], correlate: bool
+) -> Vec<Complex<T>> {
+    let len = list1.len().max(list2.len());
+    if len == 0 {
+        return vec![];
+    }
+    let mut fft_list1 = pad(list1, len, Padding::ZeroRight);
+    let mut fft_list2 = pad(list2, len, Padding::ZeroRight);
+    let mut forward = FftPlan::new(len, Direction::Forward);
+    forward.process(&mut fft_list1);
+    forward.process(&mut fft_list2);
+    if correlate {
+        fft_list2.iter_mut().for_each(|x| *x = x.conj());
+    }
+
+    let mut output = multiply_pointwise(&mut fft_list1, &mut fft_list2);
+    FftPlan::new(len, Direction::Inverse).process(&mut output);
+    output
+}
+
+/// cut a full length linear convolution of N and M samples down to `mode`
+fn trim_to_mode<T: FftFloat>(
+    mut full: Vec<Complex<T>>, n: usize, m: usize, mode: ConvolveMode
+) -> Vec<Complex<T>> {
+    let (longest, shortest) = (n.max(m), n.min(m));
+    let (start, len) = match mode {
+        ConvolveMode::Full => return full,
+        ConvolveMode::Same => ((shortest - 1) / 2, longest),
+        ConvolveMode::Valid => (shortest - 1, longest - shortest + 1),
+    };
+    full.drain(..start);
+    full.truncate(len);
+    full
+}
+
+/// point-wise multiplication; a shorter list is zero-padded on the left to
+/// match (see `multiply_pointwise_padded` to choose the padding)
 ///
 /// # Example
 ///
@@ -260,9 +761,9 @@ pub fn convolve(
 ///     Complex::new(4f64, 0f64), Complex::new(25f64, 0f64)
 /// ]);
 /// ```
-pub fn multiply_pointwise(
-    list1 :&mut Vec<Complex<f64>>, list2 :&mut Vec<Complex<f64>>
-) -> Vec<Complex<f64>> {
+pub fn multiply_pointwise<T: FftFloat>(
+    list1 :&mut Vec<Complex<T>>, list2 :&mut Vec<Complex<T>>
+) -> Vec<Complex<T>> {
     let list1_len = list1.len();
     let list2_len = list2.len();
     if list1_len != list2_len {
@@ -274,7 +775,40 @@ pub fn multiply_pointwise(
         }
     }
 
-    list1.iter().enumerate().map(|(i, v)| v.conj() * list2[i]).collect::<Vec<_>>()
+    list1.iter().enumerate().map(|(i, v)| v * list2[i]).collect::<Vec<_>>()
+}
+
+/// multiply two arrays element by element, growing the shorter one to the
+/// longer one's length with the given padding strategy first;
+/// `multiply_pointwise` is this with `Padding::ZeroLeft`
+///
+/// returns `FftError::MismatchedShapes` if the lengths differ and `padding`
+/// is `Padding::None`, rather than shifting one list against the other
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{FftError, Padding};
+///
+/// let a = &fft_rust::float_array_to_complex(vec![1f64, 2., 3.]);
+/// let b = &fft_rust::float_array_to_complex(vec![2f64, 2.]);
+/// let product = fft_rust::multiply_pointwise_padded(a, b, Padding::ZeroRight).unwrap();
+/// assert_eq!(fft_rust::complex_array_to_float(&mut product.clone()), vec![2., 4., 0.]);
+///
+/// assert!(matches!(
+///     fft_rust::multiply_pointwise_padded(a, b, Padding::None),
+///     Err(FftError::MismatchedShapes { expected: 3, found: 2 })
+/// ));
+/// ```
+pub fn multiply_pointwise_padded<T: FftFloat>(
+    list1: &[Complex<T>], list2: &[Complex<T>], padding: Padding
+) -> Result<Vec<Complex<T>>, FftError> {
+    let len = list1.len().max(list2.len());
+    if list1.len() != list2.len() && padding == Padding::None {
+        return Err(FftError::MismatchedShapes { expected: len, found: list1.len().min(list2.len()) });
+    }
+    let (list1, list2) = (pad(list1, len, padding), pad(list2, len, padding));
+    Ok(list1.iter().zip(list2.iter()).map(|(a, b)| a * b).collect())
 }
 
 
@@ -296,15 +830,148 @@ pub fn multiply_pointwise(
 /// let answer = fft_rust::zero_pad_left(arg, 1);
 /// assert_eq!(answer, &mut vec![Complex::new(0f64, 0f64), Complex::new(1f64, 0f64)]);
 /// ```
-pub fn zero_pad_left(
-    list_to_pad: &mut Vec<Complex<f64>>, num_zeros: usize
-) -> &mut Vec<Complex<f64>> {
-    for x in vec![Complex::new(0f64, 0f64); num_zeros].iter() {
+pub fn zero_pad_left<T: FftFloat>(
+    list_to_pad: &mut Vec<Complex<T>>, num_zeros: usize
+) -> &mut Vec<Complex<T>> {
+    for x in vec![float::zero(); num_zeros].iter() {
         list_to_pad.insert(0, *x);
     }
     return list_to_pad;
 }
 
+/// add N zeros as padding to the right side of an array and return the padded array;
+/// unlike `zero_pad_left` this keeps every sample at its original time index
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let arg = &mut vec![Complex::new(1f64, 0f64), Complex::new(2f64, 0f64)];
+/// let answer = fft_rust::zero_pad_right(arg, 2);
+/// assert_eq!(answer, &mut vec![
+///     Complex::new(1f64, 0f64), Complex::new(2f64, 0f64),
+///     Complex::new(0f64, 0f64), Complex::new(0f64, 0f64)
+/// ]);
+/// ```
+pub fn zero_pad_right<T: FftFloat>(
+    list_to_pad: &mut Vec<Complex<T>>, num_zeros: usize
+) -> &mut Vec<Complex<T>> {
+    let new_len = list_to_pad.len() + num_zeros;
+    list_to_pad.resize(new_len, float::zero());
+    list_to_pad
+}
+
+/// where the samples needed to grow a signal to a longer length come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /// don't pad; transforms run at the input's own length
+    None,
+    /// append zeros after the last sample
+    ZeroRight,
+    /// insert zeros before the first sample, like `fft` does; this shifts
+    /// the time origin
+    ZeroLeft,
+    /// mirror the signal back from its last sample (the last sample is repeated)
+    Symmetric,
+    /// repeat the signal from its first sample
+    Periodic,
+}
+
+/// grow `list` to `len` samples using the given padding strategy; lists that
+/// are already long enough, and `Padding::None`, are returned unchanged
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::Padding;
+///
+/// let arg = &fft_rust::float_array_to_complex(vec![1f64, 2., 3.]);
+/// let answer = fft_rust::complex_array_to_float(&mut fft_rust::pad(arg, 6, Padding::Symmetric));
+/// assert_eq!(answer, vec![1., 2., 3., 3., 2., 1.]);
+///
+/// let answer = fft_rust::complex_array_to_float(&mut fft_rust::pad(arg, 5, Padding::Periodic));
+/// assert_eq!(answer, vec![1., 2., 3., 1., 2.]);
+/// ```
+pub fn pad<T: FftFloat>(list: &[Complex<T>], len: usize, padding: Padding) -> Vec<Complex<T>> {
+    let mut padded = list.to_vec();
+    if len <= list.len() || list.is_empty() {
+        return padded;
+    }
+    let n = list.len();
+    let extra = len - n;
+    match padding {
+        Padding::None => {}
+        Padding::ZeroRight => {
+            zero_pad_right(&mut padded, extra);
+        }
+        Padding::ZeroLeft => {
+            zero_pad_left(&mut padded, extra);
+        }
+        Padding::Symmetric => padded.extend((0..extra).map(|j| {
+            let m = j % (2 * n);
+            if m < n { list[n - 1 - m] } else { list[m - n] }
+        })),
+        Padding::Periodic => padded.extend((0..extra).map(|j| list[j % n])),
+    }
+    padded
+}
+
+/// pad `p` up to the next power of two with the given strategy, then compute
+/// its FFT; `Padding::None` computes the exact length-N transform instead
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::Padding;
+///
+/// let arg = &fft_rust::float_array_to_complex(vec![2f64, 7., -1.]);
+/// assert_eq!(fft_rust::fft_padded(arg, Padding::ZeroRight).len(), 4);
+/// assert_eq!(fft_rust::fft_padded(arg, Padding::None).len(), 3);
+///
+/// // left padding is what `fft` does
+/// assert_eq!(
+///     fft_rust::fft_padded(arg, Padding::ZeroLeft),
+///     fft_rust::fft(&mut arg.clone())
+/// );
+/// ```
+pub fn fft_padded<T: FftFloat>(p: &[Complex<T>], padding: Padding) -> Vec<Complex<T>> {
+    transform_padded(p, padding, Direction::Forward)
+}
+
+/// pad `p` up to the next power of two with the given strategy, then compute
+/// its Inverse-FFT; `Padding::None` computes the exact length-N transform instead
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::Padding;
+///
+/// let arg = &fft_rust::float_array_to_complex(vec![4f64, 0., 0.]);
+/// assert_eq!(fft_rust::ifft_padded(arg, Padding::ZeroRight), vec![
+///     Complex::new(1f64, 0f64), Complex::new(1f64, 0f64),
+///     Complex::new(1f64, 0f64), Complex::new(1f64, 0f64)
+/// ]);
+/// ```
+pub fn ifft_padded<T: FftFloat>(p: &[Complex<T>], padding: Padding) -> Vec<Complex<T>> {
+    transform_padded(p, padding, Direction::Inverse)
+}
+
+fn transform_padded<T: FftFloat>(
+    p: &[Complex<T>], padding: Padding, direction: Direction
+) -> Vec<Complex<T>> {
+    let len = match padding {
+        Padding::None => p.len(),
+        _ => next_power_of_two(p.len()),
+    };
+    let mut output = pad(p, len, padding);
+    FftPlan::new(output.len(), direction).process(&mut output);
+    output
+}
+
 /// determines if the arg, 'num', is an integer power of two; returns a boolean
 ///
 /// # Examples
@@ -319,11 +986,9 @@ pub fn zero_pad_left(
 /// assert_eq!(answer, true);
 /// ```
 pub fn is_int_power_of_two(num: usize) -> bool {
-    // 1. use log2 to determine what power on base 2 gets the result "num"
-    // 2. subtract the result from the ceiling of the result
-    // 3. no diff means num was a power of 2 so return true, otherwise false
-    let sq_root = (num as f32).log2();
-    sq_root.ceil() - sq_root == 0f32
+    // a power of two has exactly one bit set; this stays exact for every
+    // usize, unlike taking an f32 log2 (which rounds 2^24 + 1 down to 2^24)
+    num.is_power_of_two()
 }
 
 /// given an integer, if its not a power of two, return the next power of two
@@ -340,11 +1005,22 @@ pub fn is_int_power_of_two(num: usize) -> bool {
 /// assert_eq!(answer, 8);
 /// ```
 pub fn next_power_of_two(num: usize) -> usize {
-    // 1. "log (base 2) of n" says, "2^? = n"
-    //     if the result is an integer, then n is a power of two
-    // 2. take the ceiling of the result of log so we get the next whole number (integer)
-    // 3. raise 2 to the ceiling result and convert to an int to get the next square
-    2_f32.powf((num as f32).log2().ceil()) as usize
+    // integer bit twiddling instead of 2^ceil(log2(n)) in f32, which loses
+    // precision past 2^24; see `try_next_power_of_two` for sizes near usize::MAX
+    num.next_power_of_two()
+}
+
+/// like `next_power_of_two`, but returns `FftError::LengthOverflow` instead
+/// of overflowing when the next power of two doesn't fit in a usize
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(fft_rust::try_next_power_of_two(5).unwrap(), 8);
+/// assert!(fft_rust::try_next_power_of_two(usize::MAX).is_err());
+/// ```
+pub fn try_next_power_of_two(num: usize) -> Result<usize, FftError> {
+    num.checked_next_power_of_two().ok_or(FftError::LengthOverflow)
 }
 
 /// given an array of floats, return an array of floats wrapped in Complex
@@ -363,9 +1039,9 @@ pub fn next_power_of_two(num: usize) -> usize {
 ///     Complex::new(6f64, 0f64)
 /// ]);
 /// ```
-pub fn float_array_to_complex(input_array :Vec<f64>) -> Vec<Complex<f64>> {
+pub fn float_array_to_complex<T: FftFloat>(input_array :Vec<T>) -> Vec<Complex<T>> {
     input_array.iter().map(|x| {
-        Complex::new(*x, 0f64)
+        Complex::new(*x, T::zero())
     }).collect()
 }
 
@@ -384,13 +1060,48 @@ pub fn float_array_to_complex(input_array :Vec<f64>) -> Vec<Complex<f64>> {
 /// ];
 /// assert_eq!(fft_rust::complex_array_to_float(complex_list), [0., 1., 2., 3., 4., 5., 6.]);
 /// ```
-pub fn complex_array_to_float(input_array :&mut Vec<Complex<f64>>) -> Vec<f64> {
-    input_array.iter().map(|x| x.abs()).collect()
+pub fn complex_array_to_float<T: FftFloat>(input_array :&mut Vec<Complex<T>>) -> Vec<T> {
+    input_array.iter().map(|x| x.norm()).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f64::consts::PI;
+
+    /// naive O(N^2) DFT used as a reference; the inverse is scaled by 1/N like `ifft`
+    pub(crate) fn dft(p: &[Complex<f64>], inverse: bool) -> Vec<Complex<f64>> {
+        let n = p.len();
+        let sign = if inverse { 1f64 } else { -1f64 };
+        let scale = if inverse { n as f64 } else { 1f64 };
+        (0..n).map(|k| {
+            p.iter().enumerate().map(|(j, x)| {
+                let angle = sign * 2.0 * PI * ((j * k) % n) as f64 / n as f64;
+                x * Complex::new(0.0, angle).exp()
+            }).sum::<Complex<f64>>() / scale
+        }).collect()
+    }
+
+    /// deterministic unit-variance Gaussian noise, from a 64-bit LCG through
+    /// the Box-Muller transform
+    pub(crate) fn noise(len: usize, seed: u64) -> Vec<f64> {
+        let mut state = seed;
+        let mut uniform = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+        };
+        (0..len).map(|_| {
+            let (u, v) = (uniform(), uniform());
+            (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+        }).collect()
+    }
+
+    /// deterministic complex noise with energy in every bin, for comparing
+    /// transforms against each other
+    pub(crate) fn signal(len: usize, seed: u64) -> Vec<Complex<f64>> {
+        let parts = noise(2 * len, seed);
+        parts.chunks_exact(2).map(|pair| Complex::new(pair[0], pair[1])).collect()
+    }
 
     #[test]
     fn fft_test() {
@@ -507,36 +1218,131 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fft_f32_test() {
+        let input = float_array_to_complex(vec![4f32, 3., -5., 1., 2., 7., -1., 0.]);
+        let expected = fft(&mut float_array_to_complex(vec![4f64, 3., -5., 1., 2., 7., -1., 0.]));
+        let output = fft(&mut input.clone());
+        for (a, b) in output.iter().zip(expected.iter()) {
+            assert!((a.re as f64 - b.re).abs() < 1e-5 && (a.im as f64 - b.im).abs() < 1e-5);
+        }
+        assert_eq!(
+            approximate_complex(&mut ifft(&mut output.clone())),
+            &mut input.clone()
+        );
+    }
+
+    #[test]
+    fn fft_exact_f32_test() {
+        let input: Vec<Complex<f32>> = signal(1000, 1).iter().map(
+            |x| Complex::new(x.re as f32, x.im as f32)
+        ).collect();
+        for (a, b) in ifft_exact(&fft_exact(&input)).iter().zip(input.iter()) {
+            assert!((a - b).norm() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn fft_exact_test() {
+        for len in [1, 2, 3, 5, 8, 9, 1000] {
+            let input = signal(len, 1);
+            let output = fft_exact(&input);
+            assert_eq!(output.len(), len);
+            for (a, b) in output.iter().zip(dft(&input, false).iter()) {
+                assert!((a - b).norm() < 1e-6);
+            }
+            for (a, b) in ifft_exact(&output).iter().zip(input.iter()) {
+                assert!((a - b).norm() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn fft_in_place_test() {
+        let input = float_array_to_complex(vec![2., 7., -1., 0., 5., 3., 3., -4.]);
+        let expected = fft(&mut input.clone());
+        let mut output = input.clone();
+        fft_in_place(&mut output);
+        for (a, b) in output.iter().zip(expected.iter()) {
+            assert!((a - b).norm() < 1e-9);
+        }
+
+        ifft_in_place(&mut output);
+        assert_eq!(approximate_complex(&mut output), &mut input.clone());
+    }
+
     #[test]
     fn convolve_test() {
-        let list_a = &mut vec![  // binary 0110 -> decimal 6
-            Complex::new(0f64, 0f64), Complex::new(1f64, 0f64),
-            Complex::new(1f64, 0f64), Complex::new(0f64, 0f64)
-        ];
-        let list_b = &mut vec![  // binary 0010 -> decimal 2
-            Complex::new(0f64, 0f64), Complex::new(0f64, 0f64),
-            Complex::new(1f64, 0f64), Complex::new(0f64, 0f64)
-        ];
-        let output = &mut convolve(list_a, list_b);
-        assert_eq!(approximate_complex(output), &mut vec![  // binary 1100 -> decimal 12
-            Complex::new(1f64, 0f64), Complex::new(1f64, 0f64),
-            Complex::new(0f64, 0f64), Complex::new(0f64, 0f64)
-        ]);
+        let list_a = &float_array_to_complex(vec![0f64, 1., 1., 0.]);  // binary 0110 -> decimal 6
+        let list_b = &float_array_to_complex(vec![0f64, 0., 1., 0.]);  // binary 0010 -> decimal 2
+        let output = &mut convolve(list_a, list_b, ConvolveMode::Full);
+        assert_eq!(
+            complex_array_to_float(approximate_complex(output)),
+            vec![0., 0., 0., 1., 1., 0., 0.]  // binary 0001100 -> decimal 12
+        );
 
-        let list_c = &mut vec![  // decimal 25
-            Complex::new(0f64, 0f64), Complex::new(0f64, 0f64),
-            Complex::new(2f64, 0f64), Complex::new(5f64, 0f64)
-        ];
-        let list_d = &mut vec![  // decimal 25
-            Complex::new(0f64, 0f64), Complex::new(0f64, 0f64),
-            Complex::new(2f64, 0f64), Complex::new(5f64, 0f64)
-        ];
-        list_c.reverse();  // why do i need to reverse this one to get the answer??
-        let output2 = &mut convolve(list_c, list_d);
-        assert_eq!(approximate_complex(output2), &mut vec![  // decimal 400 + 200 + 25 = 625
-            Complex::new(0f64, 0f64), Complex::new(4f64, 0f64),
-            Complex::new(20f64, 0f64), Complex::new(25f64, 0f64)
-        ]);
+        let list_c = &float_array_to_complex(vec![0f64, 0., 2., 5.]);  // decimal 25
+        let list_d = &float_array_to_complex(vec![0f64, 0., 2., 5.]);  // decimal 25
+        let output2 = &mut convolve(list_c, list_d, ConvolveMode::Full);
+        assert_eq!(
+            complex_array_to_float(approximate_complex(output2)),
+            vec![0., 0., 0., 0., 4., 20., 25.]  // decimal 400 + 200 + 25 = 625
+        );
+    }
+
+    /// direct O(N*M) linear convolution used as a reference
+    fn direct_convolve(a: &[Complex<f64>], b: &[Complex<f64>]) -> Vec<Complex<f64>> {
+        let mut out = vec![Complex::new(0f64, 0f64); a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                out[i + j] += x * y;
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn convolve_modes_test() {
+        let a = signal(7, 1);
+        let b: Vec<_> = (0..4).map(|x| Complex::new(1.0 - x as f64, 2.0)).collect();
+        let full = direct_convolve(&a, &b);
+        let close = |x: &[Complex<f64>], y: &[Complex<f64>]| {
+            x.len() == y.len() && x.iter().zip(y.iter()).all(|(p, q)| (p - q).norm() < 1e-9)
+        };
+        assert!(close(&convolve(&a, &b, ConvolveMode::Full), &full));
+        assert!(close(&convolve(&a, &b, ConvolveMode::Same), &full[1..8]));
+        assert!(close(&convolve(&a, &b, ConvolveMode::Valid), &full[3..7]));
+        assert!(close(&convolve(&b, &a, ConvolveMode::Valid), &full[3..7]));
+        assert!(convolve(&a, &[], ConvolveMode::Full).is_empty());
+    }
+
+    #[test]
+    fn correlate_test() {
+        let a: Vec<_> = (0..5).map(|x| Complex::new(x as f64, 1.0)).collect();
+        let b = vec![Complex::new(1f64, 1f64), Complex::new(2f64, 0f64)];
+        // numpy.correlate(a, b, 'full')
+        let expected: Vec<_> = (-1i32..5).map(|k| {
+            (0..b.len() as i32).filter(|n| (0..5).contains(&(n + k)))
+                .map(|n| a[(n + k) as usize] * b[n as usize].conj())
+                .sum::<Complex<f64>>()
+        }).collect();
+        for (x, y) in correlate(&a, &b, ConvolveMode::Full).iter().zip(expected.iter()) {
+            assert!((x - y).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn circular_test() {
+        let a = float_array_to_complex(vec![1f64, 2., 3., 4.]);
+        let b = float_array_to_complex(vec![1f64, 0., 0., 1.]);
+        assert_eq!(
+            complex_array_to_float(approximate_complex(&mut convolve_circular(&a, &b))),
+            vec![3., 5., 7., 5.]
+        );
+        assert_eq!(
+            complex_array_to_float(approximate_complex(&mut correlate_circular(&a, &b))),
+            vec![5., 3., 5., 7.]
+        );
     }
 
     #[test]
@@ -556,6 +1362,25 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn multiply_pointwise_padded_test() {
+        let a = float_array_to_complex(vec![1f64, 2., 3., 4.]);
+        let b = float_array_to_complex(vec![5f64, 6.]);
+        let product = |padding| complex_array_to_float(&mut multiply_pointwise_padded(&a, &b, padding).unwrap());
+        assert_eq!(product(Padding::ZeroRight), vec![5., 12., 0., 0.]);
+        assert_eq!(product(Padding::ZeroLeft), vec![0., 0., 15., 24.]);
+        assert_eq!(product(Padding::Periodic), vec![5., 12., 15., 24.]);
+        assert_eq!(
+            product(Padding::ZeroLeft),
+            complex_array_to_float(&mut multiply_pointwise(&mut a.clone(), &mut b.clone()))
+        );
+        assert!(matches!(
+            multiply_pointwise_padded(&b, &a, Padding::None),
+            Err(FftError::MismatchedShapes { expected: 4, found: 2 })
+        ));
+        assert_eq!(multiply_pointwise_padded(&a, &a, Padding::None).unwrap().len(), 4);
+    }
+
     #[test]
     fn approximate_complex_test() {
         let arg = &mut vec![Complex::new(1f64, 0.000001f64), Complex::new(2.00000001f64, 0f64)];
@@ -588,6 +1413,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn zero_pad_right_test() {
+        assert_eq!(
+            zero_pad_right(&mut vec![Complex::new(1f64, 0f64)], 1),
+            &mut vec![Complex::new(1f64, 0f64), Complex::new(0f64, 0f64)]
+        );
+        assert_eq!(
+            zero_pad_right(&mut vec![Complex::new(1f64, 0f64)], 0),
+            &mut vec![Complex::new(1f64, 0f64)]
+        );
+    }
+
+    #[test]
+    fn pad_test() {
+        let list = float_array_to_complex(vec![1f64, 2., 3.]);
+        let padded = |len, padding| complex_array_to_float(&mut pad(&list, len, padding));
+        assert_eq!(padded(5, Padding::None), vec![1., 2., 3.]);
+        assert_eq!(padded(5, Padding::ZeroRight), vec![1., 2., 3., 0., 0.]);
+        assert_eq!(padded(5, Padding::ZeroLeft), vec![0., 0., 1., 2., 3.]);
+        assert_eq!(padded(10, Padding::Symmetric), vec![1., 2., 3., 3., 2., 1., 1., 2., 3., 3.]);
+        assert_eq!(padded(8, Padding::Periodic), vec![1., 2., 3., 1., 2., 3., 1., 2.]);
+        assert_eq!(padded(2, Padding::Periodic), vec![1., 2., 3.]);
+    }
+
+    #[test]
+    fn fft_padded_test() {
+        let input = float_array_to_complex(vec![2f64, 7., -1., 3., 5.]);
+        for padding in [Padding::ZeroRight, Padding::ZeroLeft, Padding::Symmetric, Padding::Periodic] {
+            let output = fft_padded(&input, padding);
+            let expected = dft(&pad(&input, 8, padding), false);
+            for (a, b) in output.iter().zip(expected.iter()) {
+                assert!((a - b).norm() < 1e-9);
+            }
+            let round_trip = &mut ifft_padded(&output, Padding::None);
+            assert_eq!(approximate_complex(round_trip), &mut pad(&input, 8, padding));
+        }
+        assert_eq!(fft_padded(&input, Padding::None).len(), 5);
+    }
+
     #[test]
     fn is_power_of_two_test() {
         assert_eq!(is_int_power_of_two(0), false);
@@ -595,6 +1459,68 @@ mod tests {
         assert_eq!(is_int_power_of_two(2), true);
         assert_eq!(is_int_power_of_two(3), false);
         assert_eq!(is_int_power_of_two(4), true);
+        assert!(!is_int_power_of_two((1 << 24) + 1));
+        assert!(is_int_power_of_two(1 << 40));
+    }
+
+    #[test]
+    fn fft_parallel_test() {
+        let input = signal(3000, 1);
+        let serial = fft_parallel(&mut input.clone(), Parallelism::serial());
+        let inverse = ifft_parallel(&mut serial.clone(), Parallelism::serial());
+        for threads in [2, 4, 7] {
+            let parallelism = Parallelism { threads, min_len: 16 };
+            assert_eq!(fft_parallel(&mut input.clone(), parallelism), serial);
+            assert_eq!(ifft_parallel(&mut serial.clone(), parallelism), inverse);
+        }
+    }
+
+    #[test]
+    fn fft_batch_test() {
+        let input = signal(6 * 40, 2);
+        let mut data = input.clone();
+        fft_batch(&mut data, 6, 40);
+        for (signal, transformed) in input.chunks(6).zip(data.chunks(6)) {
+            assert_eq!(transformed, &fft_exact(signal)[..]);
+        }
+        ifft_batch(&mut data, 6, 40);
+        for (a, b) in data.iter().zip(input.iter()) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn fft_batch_rejects_wrong_count_test() {
+        fft_batch(&mut [Complex::new(1f64, 0f64); 8], 4, 3);
+    }
+
+    #[test]
+    fn try_fft_test() {
+        let empty: Vec<Complex<f64>> = vec![];
+        assert!(matches!(try_fft(&empty), Err(FftError::EmptyInput)));
+        assert!(matches!(try_ifft(&empty), Err(FftError::EmptyInput)));
+
+        let arg = vec![Complex::new(1f64, 0f64), Complex::new(0f64, f64::INFINITY)];
+        assert!(matches!(try_fft(&arg), Err(FftError::NonFiniteInput { index: 1 })));
+
+        let arg = float_array_to_complex(vec![2f64, 7., -1.]);
+        assert_eq!(try_fft(&arg).unwrap(), fft(&mut arg.clone()));
+        assert_eq!(try_ifft(&arg).unwrap(), ifft(&mut arg.clone()));
+    }
+
+    #[test]
+    fn try_convolve_test() {
+        let arg = float_array_to_complex(vec![2f64, 7., -1.]);
+        let nan = vec![Complex::new(f64::NAN, 0f64)];
+        assert!(matches!(
+            try_convolve(&arg, &nan, ConvolveMode::Full),
+            Err(FftError::NonFiniteInput { index: 0 })
+        ));
+        assert_eq!(
+            try_convolve(&arg, &arg, ConvolveMode::Same).unwrap(),
+            convolve(&arg, &arg, ConvolveMode::Same)
+        );
     }
 
     #[test]
@@ -605,6 +1531,8 @@ mod tests {
         assert_eq!(next_power_of_two(5), 8);
         assert_eq!(next_power_of_two(9), 16);
         assert_eq!(next_power_of_two(17), 32);
+        assert_eq!(next_power_of_two((1 << 24) + 1), 1 << 25);
+        assert!(try_next_power_of_two(usize::MAX).is_err());
     }
 
     #[test]
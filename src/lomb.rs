@@ -0,0 +1,331 @@
+//! the Lomb-Scargle periodogram of unevenly sampled data
+//!
+//! a least-squares fit of a sinusoid at each frequency, which needs no
+//! regular sampling: gaps and jittered timestamps are fine. for white
+//! Gaussian noise the normalized power is exponentially distributed with
+//! mean 1, which gives the false-alarm probability of a peak.
+//!
+//! evaluated directly it costs O(N) per frequency. Press and Rybicki's
+//! method instead spreads ("extirpolates") every sample onto a regular mesh
+//! so that one fft gives the trigonometric sums at every frequency of an
+//! evenly spaced grid, in O(N + M log M).
+
+use std::f64::consts::PI;
+
+use num::complex::Complex;
+
+use crate::float::{zero, FftFloat};
+use crate::plan::Direction;
+
+/// the mesh is this many times longer than the frequency grid, so the
+/// interpolating polynomials see at least this many points per cycle
+const OVERSAMPLING: usize = 8;
+/// how many mesh points each sample is spread onto
+const ORDER: usize = 8;
+
+/// what `lomb_scargle` returns; entry k of every vector is the frequency
+/// `frequencies[k]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LombScargle<T: FftFloat> {
+    /// the frequency grid, in cycles per unit of time
+    pub frequencies: Vec<T>,
+    /// the power normalized by twice the variance (Scargle, 1982), so white
+    /// Gaussian noise gives exponentially distributed powers with mean 1
+    pub power: Vec<T>,
+    /// the chance that noise alone gives a peak this high anywhere in the
+    /// grid, 1 - (1 - e^-P)^M for M independent frequencies
+    pub false_alarm: Vec<T>,
+    /// M, estimated as the number of resolution elements 1 / T that fit in
+    /// the grid for a record lasting T, between 1 and the grid's length
+    pub independent_frequencies: T,
+}
+
+impl<T: FftFloat> LombScargle<T> {
+    /// the frequency and power of the highest peak
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// // every third sample missing
+    /// let times: Vec<f64> = (0..60).filter(|n| n % 3 != 2).map(|n| n as f64).collect();
+    /// let values: Vec<f64> = times.iter().map(|t| (std::f64::consts::PI * t / 2.0).cos()).collect();
+    /// let frequencies: Vec<f64> = (1..=40).map(|k| k as f64 / 80.0).collect();
+    /// let periodogram = fft_rust::lomb_scargle(&times, &values, &frequencies);
+    /// assert_eq!(periodogram.peak().0, 0.25);
+    /// ```
+    pub fn peak(&self) -> (T, T) {
+        let k = (0..self.power.len())
+            .fold(0, |best, k| if self.power[k] > self.power[best] { k } else { best });
+        (self.frequencies[k], self.power[k])
+    }
+
+    /// the power a peak needs for a false-alarm probability of `probability`;
+    /// the inverse of `false_alarm`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let times: Vec<f64> = (0..100).map(|n| (n * n % 101) as f64).collect();
+    /// let values: Vec<f64> = times.iter().map(|t| (0.3 * t).sin()).collect();
+    /// let frequencies: Vec<f64> = (1..=500).map(|k| k as f64 / 1000.0).collect();
+    /// let periodogram = fft_rust::lomb_scargle(&times, &values, &frequencies);
+    /// let (_, power) = periodogram.peak();
+    /// assert!(power > periodogram.false_alarm_level(1e-6));
+    /// ```
+    pub fn false_alarm_level(&self, probability: T) -> T {
+        let all_clear = (T::one() - probability).powf(T::one() / self.independent_frequencies);
+        -(T::one() - all_clear).ln()
+    }
+}
+
+/// the Lomb-Scargle periodogram of `values` sampled at `times`, on the
+/// evenly spaced, increasing grid `frequencies`, in cycles per unit of time,
+/// by Press and Rybicki's fft method
+///
+/// `times` needn't be sorted or evenly spaced. the mean is taken out of
+/// `values` first. frequencies can go past the Nyquist frequency of the
+/// average spacing; uneven sampling often resolves them. panics if `times`
+/// and `values` differ in length, there are fewer than two samples, the grid
+/// is empty or isn't evenly spaced and increasing
+///
+/// # Example
+///
+/// ```
+/// // a 0.1 Hz sinusoid sampled once a second, with a long gap
+/// let times: Vec<f64> = (0..200).filter(|n| !(60..140).contains(n)).map(|n| n as f64).collect();
+/// let values: Vec<f64> = times.iter().map(|t| (2.0 * std::f64::consts::PI * 0.1 * t).sin()).collect();
+/// let frequencies: Vec<f64> = (1..=500).map(|k| k as f64 / 1000.0).collect();
+/// let periodogram = fft_rust::lomb_scargle(&times, &values, &frequencies);
+/// let (frequency, power) = periodogram.peak();
+/// assert_eq!(frequency, 0.1);
+/// // a pure sinusoid explains all of the variance
+/// assert!((power - (times.len() - 1) as f64 / 2.0).abs() < 1e-3);
+/// assert!(periodogram.false_alarm[99] < 1e-12);
+/// ```
+pub fn lomb_scargle<T: FftFloat>(times: &[T], values: &[T], frequencies: &[T]) -> LombScargle<T> {
+    assert_eq!(
+        times.len(), values.len(),
+        "there are {} times and {} values; they need to be the same length", times.len(), values.len()
+    );
+    let len = times.len();
+    assert!(len > 1, "a Lomb-Scargle periodogram needs at least two samples");
+    assert!(!frequencies.is_empty(), "the frequency grid is empty");
+    let (f0, df) = (frequencies[0], spacing(frequencies));
+
+    // time is measured from the first sample; the periodogram doesn't
+    // depend on the origin, and the trigonometric sums keep their precision
+    let start = times.iter().copied().fold(T::infinity(), T::min);
+    let end = times.iter().copied().fold(T::neg_infinity(), T::max);
+    let times: Vec<T> = times.iter().map(|t| *t - start).collect();
+    let n = T::from_usize(len);
+    let mean = values.iter().copied().sum::<T>() / n;
+    let centred: Vec<T> = values.iter().map(|y| *y - mean).collect();
+    let variance = centred.iter().map(|y| *y * *y).sum::<T>() / (n - T::one());
+
+    let two = T::from_f64(2.0);
+    let count = frequencies.len();
+    let h = trig_sums(&times, &centred, f0, df, count);
+    let doubled = trig_sums(&times, &vec![T::one(); len], two * f0, two * df, count);
+    let half = T::from_f64(0.5);
+    let tiny = T::epsilon() * n;
+    let power: Vec<T> = h.iter().zip(doubled.iter()).map(|(h, doubled)| {
+        // tau makes the sine and cosine terms orthogonal: it rotates the
+        // sum of e^(2 i omega t) onto the real axis
+        let r = doubled.norm();
+        let (cos_2tau, sin_2tau) = if r > T::zero() {
+            (doubled.re / r, doubled.im / r)
+        } else {
+            (T::one(), T::zero())
+        };
+        let cos_tau = ((T::one() + cos_2tau) * half).sqrt();
+        let sin_tau = ((T::one() - cos_2tau) * half).sqrt().copysign(sin_2tau);
+        let yc = h.re * cos_tau + h.im * sin_tau;
+        let ys = h.im * cos_tau - h.re * sin_tau;
+        let (cc, ss) = ((n + r) * half, (n - r) * half);
+        let term = |y: T, squares: T| if squares > tiny { y * y / squares } else { T::zero() };
+        (term(yc, cc) + term(ys, ss)) / (two * variance)
+    }).collect();
+
+    let span = (end - start) * (frequencies[count - 1] - f0);
+    let independent_frequencies = span.max(T::one()).min(T::from_usize(count));
+    let false_alarm = power.iter().map(|p| {
+        // 1 - (1 - e^-p)^m, without cancelling when e^-p is tiny
+        -(independent_frequencies * (-(-*p).exp()).ln_1p()).exp_m1()
+    }).collect();
+
+    LombScargle { frequencies: frequencies.to_vec(), power, false_alarm, independent_frequencies }
+}
+
+/// the step of an evenly spaced, increasing grid
+fn spacing<T: FftFloat>(frequencies: &[T]) -> T {
+    let count = frequencies.len();
+    if count == 1 {
+        return T::one();
+    }
+    let step = (frequencies[count - 1] - frequencies[0]) / T::from_usize(count - 1);
+    assert!(step > T::zero(), "the frequency grid needs to be increasing");
+    let tolerance = T::from_f64(1e-4) * step;
+    for (k, f) in frequencies.iter().enumerate() {
+        assert!(
+            (*f - (frequencies[0] + step * T::from_usize(k))).abs() <= tolerance,
+            "the frequency grid needs to be evenly spaced; frequency {} is {:?}", k, f
+        );
+    }
+    step
+}
+
+/// the sums over j of h_j e^(2 pi i f t_j) for f = f0, f0 + df, ... (`count`
+/// of them), with every t_j >= 0; each e^(2 pi i k df t_j) is a smooth
+/// function of the mesh position df t_j L (mod L), so sampling it there by
+/// interpolation turns the sums into one inverse fft of the mesh
+fn trig_sums<T: FftFloat>(times: &[T], weights: &[T], f0: T, df: T, count: usize) -> Vec<Complex<T>> {
+    let len = (OVERSAMPLING * count).max(ORDER).next_power_of_two();
+    let mut mesh = vec![zero(); len];
+    let tau = T::from_f64(2.0 * PI);
+    for (t, h) in times.iter().zip(weights.iter()) {
+        // the starting frequency's phase, reduced to a cycle first
+        let value = Complex::from_polar(*h, tau * (f0 * *t).fract());
+        extirpolate(&mut mesh, (df * *t).fract() * T::from_usize(len), value);
+    }
+    crate::batch(&mut mesh, len, 1, Direction::Inverse);
+    let scale = T::from_usize(len);
+    mesh.truncate(count);
+    mesh.iter_mut().for_each(|x| *x *= scale);
+    mesh
+}
+
+/// add `value` at position `x` of a periodic mesh, spread over the ORDER
+/// nearest points with Lagrange interpolation weights, so that summing the
+/// mesh against any polynomial of degree below ORDER gives `value` times
+/// the polynomial at `x` (Press and Rybicki, 1989)
+fn extirpolate<T: FftFloat>(mesh: &mut [Complex<T>], x: T, value: Complex<T>) {
+    let len = mesh.len() as isize;
+    let floor = x.floor();
+    let below = floor.to_isize().unwrap_or(0);
+    let index = |p: isize| p.rem_euclid(len) as usize;
+    if x == floor {
+        mesh[index(below)] += value;
+        return;
+    }
+    // the points run from `first` to `first + ORDER - 1`, with x between
+    // the middle two
+    let first = below - (ORDER as isize / 2 - 1);
+    let fraction = (x - floor).to_f64().unwrap_or(0.0) + (ORDER / 2 - 1) as f64;
+    let product: f64 = (0..ORDER).map(|j| fraction - j as f64).product();
+    for m in 0..ORDER {
+        // prod over j != m of (m - j)
+        let denominator: f64 = (0..ORDER).filter(|j| *j != m).map(|j| m as f64 - j as f64).product();
+        let weight = product / ((fraction - m as f64) * denominator);
+        mesh[index(first + m as isize)] += value * T::from_f64(weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::noise;
+
+    /// the textbook O(N) per frequency evaluation
+    fn direct(times: &[f64], values: &[f64], frequencies: &[f64]) -> Vec<f64> {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|y| (y - mean) * (y - mean)).sum::<f64>() / (n - 1.0);
+        frequencies.iter().map(|f| {
+            let omega = 2.0 * PI * f;
+            let s2: f64 = times.iter().map(|t| (2.0 * omega * t).sin()).sum();
+            let c2: f64 = times.iter().map(|t| (2.0 * omega * t).cos()).sum();
+            let tau = s2.atan2(c2) / (2.0 * omega);
+            let (mut yc, mut ys, mut cc, mut ss) = (0.0, 0.0, 0.0, 0.0);
+            for (t, y) in times.iter().zip(values.iter()) {
+                let (s, c) = (omega * (t - tau)).sin_cos();
+                yc += (y - mean) * c;
+                ys += (y - mean) * s;
+                cc += c * c;
+                ss += s * s;
+            }
+            (yc * yc / cc + ys * ys / ss) / (2.0 * variance)
+        }).collect()
+    }
+
+    /// `len` sorted times, jittered around one per unit, starting at `start`
+    fn jittered(len: usize, start: f64, seed: u64) -> Vec<f64> {
+        noise(len, seed).iter().enumerate().map(|(n, x)| start + n as f64 + 0.3 * x.tanh()).collect()
+    }
+
+    #[test]
+    fn matches_direct_test() {
+        let times = jittered(500, 1000.0, 1);
+        let disturbance = noise(500, 2);
+        let values: Vec<f64> = times.iter().zip(disturbance.iter()).map(
+            |(t, e)| 3.0 + (2.0 * PI * 0.137 * t).cos() + e
+        ).collect();
+        let frequencies: Vec<f64> = (0..700).map(|k| 0.01 + k as f64 * 0.001).collect();
+        let fast = lomb_scargle(&times, &values, &frequencies);
+        let expected = direct(&times, &values, &frequencies);
+        for (a, b) in fast.power.iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-3, "{} differs from {}", a, b);
+        }
+        assert!((fast.peak().0 - 0.137).abs() < 1e-3);
+    }
+
+    #[test]
+    fn even_sampling_test() {
+        // on evenly spaced samples, at the Fourier frequencies, the power is
+        // the periodogram |Y_k|^2 / (N variance)
+        let len = 64;
+        let values = noise(len, 3);
+        let times: Vec<f64> = (0..len).map(|n| n as f64).collect();
+        let frequencies: Vec<f64> = (1..len / 2).map(|k| k as f64 / len as f64).collect();
+        let periodogram = lomb_scargle(&times, &values, &frequencies);
+        let mean = values.iter().sum::<f64>() / len as f64;
+        let variance = values.iter().map(|y| (y - mean) * (y - mean)).sum::<f64>() / (len - 1) as f64;
+        let spectrum = crate::rfft(&values);
+        for (k, p) in periodogram.power.iter().enumerate() {
+            let expected = spectrum[k + 1].norm_sqr() / (len as f64 * variance);
+            assert!((p - expected).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn noise_power_test() {
+        // noise powers are exponential with mean 1, and the highest of them
+        // is no surprise
+        let times = jittered(2000, 0.0, 5);
+        let values = noise(2000, 6);
+        let frequencies: Vec<f64> = (1..=1000).map(|k| k as f64 / 2000.0).collect();
+        let periodogram = lomb_scargle(&times, &values, &frequencies);
+        let mean = periodogram.power.iter().sum::<f64>() / 1000.0;
+        assert!((mean - 1.0).abs() < 0.1);
+        let lowest = periodogram.false_alarm.iter().copied().fold(1.0, f64::min);
+        assert!(lowest > 0.01);
+        assert!((periodogram.independent_frequencies - 1000.0).abs() < 2.0);
+    }
+
+    #[test]
+    fn false_alarm_level_test() {
+        let times = jittered(100, 0.0, 7);
+        let values = noise(100, 8);
+        let frequencies: Vec<f64> = (1..=50).map(|k| k as f64 / 100.0).collect();
+        let periodogram = lomb_scargle(&times, &values, &frequencies);
+        for (p, probability) in periodogram.power.iter().zip(periodogram.false_alarm.iter()) {
+            if *probability < 1.0 - 1e-9 {
+                assert!((periodogram.false_alarm_level(*probability) - p).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn lomb_scargle_f32_test() {
+        let times: Vec<f32> = jittered(300, 0.0, 9).iter().map(|t| *t as f32).collect();
+        let values: Vec<f32> = times.iter().map(|t| (2.0 * std::f32::consts::PI * 0.2 * t).sin()).collect();
+        let frequencies: Vec<f32> = (1..=400).map(|k| k as f32 / 1000.0).collect();
+        let periodogram = lomb_scargle(&times, &values, &frequencies);
+        assert!((periodogram.peak().0 - 0.2).abs() < 2e-3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn lomb_scargle_rejects_uneven_grid_test() {
+        lomb_scargle(&[0f64, 1., 2.], &[1., 0., 1.], &[0.1, 0.2, 0.4]);
+    }
+}
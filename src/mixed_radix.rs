@@ -0,0 +1,238 @@
+//! mixed-radix Cooley-Tukey fft
+//!
+//! a length N = r * m transform is split into r sub-transforms of length m
+//! (every r-th sample), which are then combined with twiddled radix-r
+//! butterflies. radix 2, 3, 4 and 5 butterflies are written out by hand; any
+//! larger prime factor is done with a length-p plan (Rader or Bluestein).
+
+use std::f64::consts::PI;
+use num::complex::Complex;
+
+use crate::float::{twiddle, zero, FftFloat};
+use crate::plan::{Algorithm, Direction, FftPlan};
+use crate::simd;
+
+#[derive(Debug, Clone)]
+pub(crate) struct MixedRadix<T: FftFloat> {
+    len: usize,
+    inverse: bool,
+    // radices, outermost split first; their product is len
+    factors: Vec<usize>,
+    butterflies: Butterflies<T>,
+    scratch: Vec<Complex<T>>,
+}
+
+#[derive(Debug, Clone)]
+struct Butterflies<T: FftFloat> {
+    inverse: bool,
+    // e^(sign * 2i * pi * k / len) for k in 0..len
+    twiddles: Vec<Complex<T>>,
+    // forward plans for prime factors above 5; the inverse is taken as
+    // conj(fft(conj(x))) so no 1/p scaling sneaks in
+    generic: Vec<FftPlan<T>>,
+    buffer: Vec<Complex<T>>,
+}
+
+impl<T: FftFloat> MixedRadix<T> {
+    pub(crate) fn new(len: usize, direction: Direction, algorithm: Algorithm) -> MixedRadix<T> {
+        let inverse = direction == Direction::Inverse;
+        let sign = if inverse { 1f64 } else { -1f64 };
+        let factors = factorize(len);
+
+        let mut generic: Vec<FftPlan<T>> = vec![];
+        for factor in factors.iter().filter(|f| **f > 5) {
+            if !generic.iter().any(|plan| plan.len() == *factor) {
+                generic.push(FftPlan::with_algorithm(*factor, Direction::Forward, algorithm));
+            }
+        }
+        let largest = factors.iter().copied().max().unwrap_or(1);
+
+        MixedRadix {
+            len,
+            inverse,
+            factors,
+            butterflies: Butterflies {
+                inverse,
+                twiddles: (0..len).map(|k| twiddle(k, len, sign)).collect(),
+                generic,
+                buffer: vec![zero(); largest],
+            },
+            scratch: vec![zero(); len],
+        }
+    }
+
+    pub(crate) fn process(&mut self, data: &mut [Complex<T>]) {
+        self.scratch.copy_from_slice(data);
+        mixed_radix_fft(&self.scratch, 1, data, &self.factors, 1, &mut self.butterflies);
+        if self.inverse {
+            let scale = T::from_usize(self.len);
+            for x in data.iter_mut() {
+                *x /= scale;
+            }
+        }
+    }
+}
+
+/// fft of every `stride`-th element of `input` into `output`, splitting by
+/// `factors[0]` and recursing on the rest
+fn mixed_radix_fft<T: FftFloat>(
+    input: &[Complex<T>], stride: usize, output: &mut [Complex<T>],
+    factors: &[usize], twiddle_stride: usize, butterflies: &mut Butterflies<T>
+) {
+    let radix = factors[0];
+    let m = output.len() / radix;
+
+    if m == 1 {
+        for (j, y) in output.iter_mut().enumerate() {
+            *y = input[j * stride];
+        }
+        butterflies.dft(output);
+        return;
+    }
+
+    for (j, chunk) in output.chunks_exact_mut(m).enumerate() {
+        mixed_radix_fft(
+            &input[j * stride..], stride * radix, chunk,
+            &factors[1..], twiddle_stride * radix, butterflies
+        );
+    }
+
+    // output[j*m + k1] holds sub-transform j at bin k1; bin k1 + m*k2 of the
+    // full transform is the radix-point DFT over j of the twiddled values
+    if radix == 4 {
+        simd::radix4(output, &butterflies.twiddles, twiddle_stride, butterflies.inverse);
+        return;
+    }
+    let mut column = std::mem::take(&mut butterflies.buffer);
+    for k1 in 0..m {
+        for (j, c) in column[..radix].iter_mut().enumerate() {
+            *c = output[j * m + k1] * butterflies.twiddles[j * k1 * twiddle_stride];
+        }
+        butterflies.dft(&mut column[..radix]);
+        for (k2, c) in column[..radix].iter().enumerate() {
+            output[k1 + m * k2] = *c;
+        }
+    }
+    butterflies.buffer = column;
+}
+
+impl<T: FftFloat> Butterflies<T> {
+    /// unscaled DFT of `x` in place, in this plan's direction
+    fn dft(&mut self, x: &mut [Complex<T>]) {
+        let sign = if self.inverse { 1f64 } else { -1f64 };
+        match x.len() {
+            1 => {}
+            2 => {
+                let (a, b) = (x[0], x[1]);
+                x[0] = a + b;
+                x[1] = a - b;
+            }
+            3 => {
+                let sin_60 = T::from_f64(sign * (2.0 * PI / 3.0).sin());
+                let sum = x[1] + x[2];
+                let diff = rotate(x[1] - x[2]) * sin_60;
+                let mid = x[0] - sum / T::from_f64(2.0);
+                x[0] += sum;
+                x[1] = mid + diff;
+                x[2] = mid - diff;
+            }
+            4 => {
+                let (a, b, c, d) = (x[0], x[1], x[2], x[3]);
+                let diff = rotate(b - d) * T::from_f64(sign);
+                x[0] = a + b + c + d;
+                x[1] = a - c + diff;
+                x[2] = a + c - b - d;
+                x[3] = a - c - diff;
+            }
+            5 => {
+                let c1 = T::from_f64((2.0 * PI / 5.0).cos());
+                let c2 = T::from_f64((4.0 * PI / 5.0).cos());
+                let s1 = T::from_f64(sign * (2.0 * PI / 5.0).sin());
+                let s2 = T::from_f64(sign * (4.0 * PI / 5.0).sin());
+                let (sum_14, sum_23) = (x[1] + x[4], x[2] + x[3]);
+                let (diff_14, diff_23) = (rotate(x[1] - x[4]), rotate(x[2] - x[3]));
+                let x_0 = x[0];
+                let real_1 = x_0 + sum_14 * c1 + sum_23 * c2;
+                let real_2 = x_0 + sum_14 * c2 + sum_23 * c1;
+                let imag_1 = diff_14 * s1 + diff_23 * s2;
+                let imag_2 = diff_14 * s2 - diff_23 * s1;
+                x[0] = x_0 + sum_14 + sum_23;
+                x[1] = real_1 + imag_1;
+                x[2] = real_2 + imag_2;
+                x[3] = real_2 - imag_2;
+                x[4] = real_1 - imag_1;
+            }
+            p => {
+                let plan = self.generic.iter_mut().find(|plan| plan.len() == p)
+                    .expect("no plan for prime factor");
+                if self.inverse {
+                    x.iter_mut().for_each(|c| *c = c.conj());
+                    plan.process(x);
+                    x.iter_mut().for_each(|c| *c = c.conj());
+                } else {
+                    plan.process(x);
+                }
+            }
+        }
+    }
+}
+
+/// multiply by i
+fn rotate<T: FftFloat>(c: Complex<T>) -> Complex<T> {
+    Complex::new(-c.im, c.re)
+}
+
+/// split `len` into butterfly radices: 4s first, then 2, 3, 5 and any larger primes
+pub(crate) fn factorize(mut len: usize) -> Vec<usize> {
+    let mut factors = vec![];
+    for radix in [4, 2, 3, 5] {
+        while len.is_multiple_of(radix) && len > 1 {
+            factors.push(radix);
+            len /= radix;
+        }
+    }
+    let mut p = 7;
+    while len > 1 {
+        while len.is_multiple_of(p) {
+            factors.push(p);
+            len /= p;
+        }
+        p += 2;
+        if p * p > len && len > 1 {
+            factors.push(len);
+            len = 1;
+        }
+    }
+    factors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{dft, signal};
+
+    #[test]
+    fn mixed_radix_matches_dft_test() {
+        for len in [2, 3, 4, 5, 6, 12, 15, 30, 49, 77, 480, 1000, 3 * 23] {
+            let input = signal(len, 1);
+            for (direction, inverse) in [(Direction::Forward, false), (Direction::Inverse, true)] {
+                let mut output = input.clone();
+                MixedRadix::<f64>::new(len, direction, Algorithm::Recursive).process(&mut output);
+                for (a, b) in output.iter().zip(dft(&input, inverse).iter()) {
+                    assert!((a - b).norm() < 1e-8, "length {} differs", len);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn factorize_test() {
+        assert_eq!(factorize(1), vec![]);
+        assert_eq!(factorize(480), vec![4, 4, 2, 3, 5]);
+        assert_eq!(factorize(1000), vec![4, 2, 5, 5, 5]);
+        assert_eq!(factorize(3072), vec![4, 4, 4, 4, 4, 3]);
+        assert_eq!(factorize(77), vec![7, 11]);
+        assert_eq!(factorize(49), vec![7, 7]);
+        assert_eq!(factorize(2 * 101), vec![2, 101]);
+    }
+}
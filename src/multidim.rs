@@ -0,0 +1,299 @@
+//! multi-dimensional ffts
+//!
+//! a 2-D DFT is a 1-D DFT of every row followed by a 1-D DFT of every column.
+//! the columns are transposed into rows first, so both passes run as one
+//! batch of contiguous signals with a single plan (and threads, for large
+//! inputs), then transposed back.
+//!
+//! N-D transforms work the same way one axis at a time, except the signals
+//! along an inner axis are transformed where they lie, with a stride.
+
+use num::complex::Complex;
+
+use crate::float::{zero, FftFloat};
+use crate::parallel::{self, Parallelism};
+use crate::plan::{Direction, FftPlan};
+
+/// compute the 2-D DFT of a `width` x `height` row-major buffer in place
+///
+/// nothing is padded and the phase is kept, so `ifft2` undoes it exactly (up
+/// to rounding); panics if `data.len()` isn't `width * height`
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// // 2 x 2 image: [[1, 2], [3, 4]]
+/// let data = &mut fft_rust::float_array_to_complex(vec![1f64, 2., 3., 4.]);
+/// fft_rust::fft2(data, 2, 2);
+/// assert_eq!(data, &fft_rust::float_array_to_complex(vec![10f64, -2., -4., 0.]));
+///
+/// fft_rust::ifft2(data, 2, 2);
+/// assert_eq!(data, &fft_rust::float_array_to_complex(vec![1f64, 2., 3., 4.]));
+/// ```
+pub fn fft2<T: FftFloat>(data: &mut [Complex<T>], width: usize, height: usize) {
+    transform2(data, width, height, Direction::Forward)
+}
+
+/// compute the 2-D Inverse-DFT of a `width` x `height` row-major buffer in
+/// place, scaled by 1/(width * height) (see `fft2`)
+pub fn ifft2<T: FftFloat>(data: &mut [Complex<T>], width: usize, height: usize) {
+    transform2(data, width, height, Direction::Inverse)
+}
+
+fn transform2<T: FftFloat>(
+    data: &mut [Complex<T>], width: usize, height: usize, direction: Direction
+) {
+    assert_eq!(
+        Some(data.len()), width.checked_mul(height),
+        "expected a {} x {} buffer, found {} samples", width, height, data.len()
+    );
+    if data.is_empty() {
+        return;
+    }
+    crate::batch(data, width, height, direction);
+
+    let mut columns = vec![zero(); data.len()];
+    transpose(data, &mut columns, width, height);
+    crate::batch(&mut columns, height, width, direction);
+    transpose(&columns, data, height, width);
+}
+
+/// compute the N-D DFT of a row-major (last axis contiguous) buffer with the
+/// given `shape` in place, transforming along each of `axes` in turn
+///
+/// `axes` can be any subset of `0..shape.len()`, in any order; the lengths
+/// along them needn't be powers of two. panics if an axis is out of range or
+/// `data.len()` isn't the product of `shape`
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// // a 2 x 2 x 2 cube of ones transforms to a single spike at the origin
+/// let data = &mut vec![Complex::new(1f64, 0f64); 8];
+/// fft_rust::fftn(data, &[2, 2, 2], &[0, 1, 2]);
+/// assert_eq!(data[0], Complex::new(8f64, 0f64));
+/// assert!(data[1..].iter().all(|x| *x == Complex::new(0f64, 0f64)));
+///
+/// // only along the last axis: each length-2 row becomes [2, 0]
+/// let data = &mut vec![Complex::new(1f64, 0f64); 8];
+/// fft_rust::fftn(data, &[2, 2, 2], &[2]);
+/// assert_eq!(data[..2], [Complex::new(2f64, 0f64), Complex::new(0f64, 0f64)]);
+/// ```
+pub fn fftn<T: FftFloat>(data: &mut [Complex<T>], shape: &[usize], axes: &[usize]) {
+    transform_n(data, shape, axes, Direction::Forward)
+}
+
+/// compute the N-D Inverse-DFT of a row-major buffer in place along `axes`,
+/// scaled by 1 over the product of the transformed lengths (see `fftn`)
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let input: Vec<_> = (0..24).map(|x| Complex::new(x as f64, 0f64)).collect();
+/// let data = &mut input.clone();
+/// fft_rust::fftn(data, &[2, 3, 4], &[0, 1, 2]);
+/// fft_rust::ifftn(data, &[2, 3, 4], &[0, 1, 2]);
+/// assert_eq!(fft_rust::approximate_complex(data), &input);
+/// ```
+pub fn ifftn<T: FftFloat>(data: &mut [Complex<T>], shape: &[usize], axes: &[usize]) {
+    transform_n(data, shape, axes, Direction::Inverse)
+}
+
+fn transform_n<T: FftFloat>(
+    data: &mut [Complex<T>], shape: &[usize], axes: &[usize], direction: Direction
+) {
+    let size = shape.iter().try_fold(1usize, |size, len| size.checked_mul(*len));
+    assert_eq!(
+        Some(data.len()), size,
+        "expected a {:?} buffer, found {} samples", shape, data.len()
+    );
+    for axis in axes {
+        assert!(*axis < shape.len(), "axis {} is out of range for shape {:?}", axis, shape);
+    }
+    if data.is_empty() {
+        return;
+    }
+
+    let parallelism = Parallelism::default();
+    for axis in axes {
+        let len = shape[*axis];
+        // samples between neighbours along `axis`
+        let stride: usize = shape[axis + 1..].iter().product();
+        let mut plan = FftPlan::new(len, direction);
+        if stride == 1 {
+            plan.set_parallelism(parallelism);
+            plan.process_batch(data);
+            continue;
+        }
+
+        // every block of len * stride samples holds `stride` interleaved signals
+        let block = len * stride;
+        let each_block = |blocks: &mut [Complex<T>]| {
+            let mut plan = plan.clone();
+            for block in blocks.chunks_exact_mut(block) {
+                for offset in 0..stride {
+                    plan.process_strided(block, offset, stride);
+                }
+            }
+        };
+        if data.len() > block && parallelism.splits(data.len()) {
+            parallel::for_each_run(data, block, parallelism.threads, each_block);
+        } else {
+            each_block(data);
+        }
+    }
+}
+
+/// write the transpose of the `width` x `height` row-major `input` to `output`
+pub(crate) fn transpose<T: Copy>(input: &[T], output: &mut [T], width: usize, height: usize) {
+    // square tiles keep both the reads and the writes cache friendly
+    const TILE: usize = 16;
+    for row_start in (0..height).step_by(TILE) {
+        for column_start in (0..width).step_by(TILE) {
+            for row in row_start..(row_start + TILE).min(height) {
+                for column in column_start..(column_start + TILE).min(width) {
+                    output[column * height + row] = input[row * width + column];
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{dft, signal};
+
+    /// the 2-D DFT straight from the definition, as rows then columns of `dft`
+    fn dft2(data: &[Complex<f64>], width: usize) -> Vec<Complex<f64>> {
+        let rows: Vec<_> = data.chunks(width).flat_map(|row| dft(row, false)).collect();
+        let mut output = rows.clone();
+        for column in 0..width {
+            let signal: Vec<_> = rows[column..].iter().step_by(width).copied().collect();
+            for (row, x) in dft(&signal, false).into_iter().enumerate() {
+                output[row * width + column] = x;
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn fft2_matches_dft_test() {
+        for (width, height) in [(1, 1), (4, 1), (1, 5), (8, 8), (6, 10), (17, 3)] {
+            let input = signal(width * height, 1);
+            let mut data = input.clone();
+            fft2(&mut data, width, height);
+            for (a, b) in data.iter().zip(dft2(&input, width).iter()) {
+                assert!((a - b).norm() < 1e-9, "{} x {} differs", width, height);
+            }
+            ifft2(&mut data, width, height);
+            for (a, b) in data.iter().zip(input.iter()) {
+                assert!((a - b).norm() < 1e-9, "{} x {} does not round trip", width, height);
+            }
+        }
+    }
+
+    #[test]
+    fn fftn_matches_fft2_test() {
+        // a 2-D transform is fftn over both axes of [height, width]
+        let (width, height) = (6, 5);
+        let input = signal(width * height, 2);
+        let mut expected = input.clone();
+        fft2(&mut expected, width, height);
+        for axes in [[0, 1], [1, 0]] {
+            let mut data = input.clone();
+            fftn(&mut data, &[height, width], &axes);
+            for (a, b) in data.iter().zip(expected.iter()) {
+                assert!((a - b).norm() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn fftn_large_test() {
+        // big enough for the default parallelism to split the strided axis
+        // across threads; each 64 x 64 slice is an independent 2-D transform,
+        // rows first like `fft2`
+        let shape = [4, 64, 64];
+        let input = signal(4 * 64 * 64, 3);
+        let mut data = input.clone();
+        fftn(&mut data, &shape, &[2, 1]);
+        let mut expected = input.clone();
+        for slice in expected.chunks_exact_mut(64 * 64) {
+            fft2(slice, 64, 64);
+        }
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn fftn_single_axis_test() {
+        // along the middle axis of a 2 x 3 x 4 cube, every (i, k) pair is a
+        // length-3 signal with stride 4
+        let shape = [2, 3, 4];
+        let input = signal(24, 4);
+        let mut data = input.clone();
+        fftn(&mut data, &shape, &[1]);
+        for i in 0..2 {
+            for k in 0..4 {
+                let signal: Vec<_> = (0..3).map(|j| input[i * 12 + j * 4 + k]).collect();
+                for (j, x) in dft(&signal, false).iter().enumerate() {
+                    assert!((data[i * 12 + j * 4 + k] - x).norm() < 1e-9);
+                }
+            }
+        }
+
+        ifftn(&mut data, &shape, &[1]);
+        for (a, b) in data.iter().zip(input.iter()) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn fftn_4d_round_trip_test() {
+        let shape = [3, 2, 5, 4];
+        let input = signal(120, 5);
+        let mut data = input.clone();
+        fftn(&mut data, &shape, &[0, 1, 2, 3]);
+        // the DC bin is the sum of everything
+        let sum: Complex<f64> = input.iter().sum();
+        assert!((data[0] - sum).norm() < 1e-9);
+        ifftn(&mut data, &shape, &[3, 2, 1, 0]);
+        for (a, b) in data.iter().zip(input.iter()) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn fftn_rejects_bad_axis_test() {
+        fftn(&mut [Complex::new(1f64, 0f64); 4], &[2, 2], &[2]);
+    }
+
+    #[test]
+    fn transpose_test() {
+        let input: Vec<_> = (0..6).collect();
+        let mut output = vec![0; 6];
+        transpose(&input, &mut output, 3, 2);
+        assert_eq!(output, vec![0, 3, 1, 4, 2, 5]);
+
+        let input: Vec<_> = (0..40 * 33).collect();
+        let mut output = vec![0; 40 * 33];
+        let mut round_trip = vec![0; 40 * 33];
+        transpose(&input, &mut output, 40, 33);
+        transpose(&output, &mut round_trip, 33, 40);
+        assert_eq!(output[1], 40);
+        assert_eq!(round_trip, input);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fft2_rejects_wrong_size_test() {
+        fft2(&mut [Complex::new(1f64, 0f64); 5], 2, 2);
+    }
+}
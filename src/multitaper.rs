@@ -0,0 +1,536 @@
+//! multitaper spectral estimation
+//!
+//! instead of averaging the periodograms of short segments, Thomson's method
+//! averages periodograms of the whole record taken through several
+//! orthogonal tapers. the discrete prolate spheroidal sequences (DPSS, or
+//! Slepian tapers) are the windows of a given length that keep the most of
+//! their energy within a bandwidth W; the first 2NW - 1 of them keep almost
+//! all of it, so each gives a nearly independent, low-leakage estimate and
+//! none of the record is thrown away.
+//!
+//! the tapers are the eigenvectors of a symmetric tridiagonal matrix that
+//! commutes with the sinc kernel defining them, found by bisection and
+//! inverse iteration; the conventions (unit energy, signs, concentration
+//! ratios) are those of `scipy.signal.windows.dpss`.
+
+use std::f64::consts::PI;
+
+use num::complex::Complex;
+
+use crate::float::{zero, FftFloat};
+use crate::plan::Direction;
+use crate::spectrum::folded;
+
+/// how `multitaper_psd` combines the eigenspectra of the tapers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaperWeighting {
+    /// the plain mean, as if every taper were perfectly concentrated
+    Equal,
+    /// Thomson's adaptive weights, which play down the poorly concentrated
+    /// tapers where the spectrum is low and their leakage would dominate
+    #[default]
+    Adaptive,
+}
+
+/// what `multitaper_psd` returns; bin k of every field is the frequency
+/// `frequencies[k]`, from DC up to Nyquist
+#[derive(Debug, Clone, PartialEq)]
+pub struct Multitaper<T: FftFloat> {
+    /// the frequency of each bin, in the units of the sample rate
+    pub frequencies: Vec<T>,
+    /// one-sided power spectral density in V^2/Hz
+    pub psd: Vec<T>,
+    /// the estimate's equivalent degrees of freedom: it is distributed as
+    /// chi-squared with this many, scaled, so 2K for equal weighting and less
+    /// where adaptive weighting has discounted tapers
+    pub degrees_of_freedom: Vec<T>,
+    /// Thomson's harmonic F statistic: how much better a sinusoid at exactly
+    /// this frequency explains the tapered spectra than noise does
+    pub f_statistic: Vec<T>,
+    /// the chance of an F statistic at least this large from noise alone,
+    /// from the F distribution with 2 and 2K - 2 degrees of freedom
+    pub p_values: Vec<T>,
+}
+
+impl<T: FftFloat> Multitaper<T> {
+    /// the frequencies of the line components the F-test finds: bins whose
+    /// p-value is below `significance` and whose F statistic is a local peak
+    ///
+    /// a line raises the F statistic of every bin within the bandwidth, so
+    /// only the peak is reported. with N bins, about N times `significance`
+    /// of them are false alarms; 1 / N is a common choice
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fft_rust::TaperWeighting;
+    ///
+    /// // a 100 Hz sinusoid in noise, sampled at 1 kHz
+    /// let mut state = 1u32;
+    /// let signal: Vec<f64> = (0..1000).map(|n| {
+    ///     state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+    ///     let noise = state as f64 / u32::MAX as f64 - 0.5;
+    ///     (2.0 * std::f64::consts::PI * 100.0 * n as f64 / 1000.0).sin() + noise
+    /// }).collect();
+    /// let estimate = fft_rust::multitaper_psd(&signal, 1000., 4.0, 7, 1000, TaperWeighting::Adaptive);
+    /// assert_eq!(estimate.lines(1e-4), vec![100.0]);
+    /// ```
+    pub fn lines(&self, significance: T) -> Vec<T> {
+        let f = &self.f_statistic;
+        (0..f.len()).filter(|k| {
+            let rising = *k == 0 || f[*k] > f[k - 1];
+            let falling = k + 1 == f.len() || f[*k] >= f[k + 1];
+            self.p_values[*k] < significance && rising && falling
+        }).map(|k| self.frequencies[k]).collect()
+    }
+}
+
+/// compute the first `count` discrete prolate spheroidal sequences of length
+/// `len` with time-halfbandwidth product `nw`, like
+/// `scipy.signal.windows.dpss`; returns the tapers and their concentration
+/// ratios, the fraction of each taper's energy within `nw / len` cycles per
+/// sample of DC
+///
+/// the tapers are orthonormal. the symmetric ones (even orders) have a
+/// positive sum and the antisymmetric ones start with a positive lobe. the
+/// concentrations are close to 1 for the first 2 `nw` - 1 tapers and fall
+/// quickly after that. panics unless 0 < `nw` < `len` / 2 and `count` is at
+/// most `len`
+///
+/// # Example
+///
+/// ```
+/// let (tapers, concentrations) = fft_rust::dpss::<f64>(128, 2.5, 4);
+/// assert_eq!(tapers.len(), 4);
+/// let energy: f64 = tapers[0].iter().map(|x| x * x).sum();
+/// assert!((energy - 1.0).abs() < 1e-12);
+/// let cross: f64 = tapers[0].iter().zip(tapers[2].iter()).map(|(a, b)| a * b).sum();
+/// assert!(cross.abs() < 1e-12);
+/// assert!(concentrations[0] > 0.9999);
+/// ```
+pub fn dpss<T: FftFloat>(len: usize, nw: f64, count: usize) -> (Vec<Vec<T>>, Vec<T>) {
+    assert!(
+        nw > 0.0 && nw < len as f64 / 2.0,
+        "the time-halfbandwidth product {} must lie between 0 and half the length {}", nw, len
+    );
+    assert!(count <= len, "there are only {} tapers of length {}, not {}", len, len, count);
+    let w = nw / len as f64;
+
+    // the tridiagonal matrix whose eigenvectors are the tapers, largest
+    // eigenvalue first (Percival and Walden, 1993, eq. 378)
+    let diagonal: Vec<f64> = (0..len).map(|n| {
+        let x = (len as f64 - 1.0 - 2.0 * n as f64) / 2.0;
+        x * x * (2.0 * PI * w).cos()
+    }).collect();
+    let off_diagonal: Vec<f64> = (1..len).map(|n| (n * (len - n)) as f64 / 2.0).collect();
+
+    let mut tapers: Vec<Vec<f64>> = Vec::with_capacity(count);
+    for k in 0..count {
+        let eigenvalue = eigenvalue(&diagonal, &off_diagonal, len - 1 - k);
+        let mut taper = eigenvector(&diagonal, &off_diagonal, eigenvalue, &tapers);
+
+        // by convention (Percival and Walden, 1993, p. 379) symmetric tapers
+        // have a positive mean and antisymmetric ones start positive, taking
+        // the first sample clear of the noise as the start of the first lobe
+        let flip = if k % 2 == 0 {
+            taper.iter().sum::<f64>() < 0.0
+        } else {
+            let threshold = (1.0 / len as f64).max(1e-7);
+            taper.iter().find(|x| x.abs() > threshold).is_some_and(|x| *x < 0.0)
+        };
+        if flip {
+            taper.iter_mut().for_each(|x| *x = -*x);
+        }
+        tapers.push(taper);
+    }
+
+    let concentrations = concentrations(&tapers, w);
+    (
+        tapers.into_iter().map(|taper| taper.into_iter().map(T::from_f64).collect()).collect(),
+        concentrations.into_iter().map(T::from_f64).collect(),
+    )
+}
+
+/// estimate the one-sided power spectral density of a real signal sampled
+/// at `fs` with Thomson's multitaper method, using the first `tapers` DPSS
+/// with time-halfbandwidth product `nw`; also runs the harmonic F-test for
+/// line components at every bin
+///
+/// the tapered signal is zero-padded to `fft_len` samples, which only
+/// interpolates the spectrum: the estimate is smoothed over `nw` / N cycles
+/// per sample either side of each frequency for a signal of N samples, and
+/// padding just samples it more finely. the F-test needs that, since it
+/// only finds lines within a small fraction of a bin of a frequency it
+/// tests; 4 or 8 times the signal length is plenty
+///
+/// `tapers` is usually 2 `nw` - 1, e.g. `nw` 4 with 7 tapers; more than that
+/// brings in tapers that leak. nothing is detrended, so subtract the mean
+/// first if the low bins matter. the F-test needs at least two tapers and is
+/// NaN with one. panics if `signal` is empty, `tapers` is zero, `fft_len`
+/// is shorter than `signal` or `nw` is out of range for `dpss`
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::TaperWeighting;
+///
+/// // white noise of variance 1/12 sampled at 100 Hz has a density of
+/// // 2 (1/12) / 100 V^2/Hz
+/// let mut state = 1u32;
+/// let signal: Vec<f64> = (0..4096).map(|_| {
+///     state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+///     state as f64 / u32::MAX as f64 - 0.5
+/// }).collect();
+/// let estimate = fft_rust::multitaper_psd(&signal, 100., 4.0, 7, 4096, TaperWeighting::Adaptive);
+/// let psd = &estimate.psd[1..2048];
+/// let mean = psd.iter().sum::<f64>() / psd.len() as f64;
+/// assert!((mean - 2.0 / 12.0 / 100.0).abs() < 1e-4);
+/// ```
+pub fn multitaper_psd<T: FftFloat>(
+    signal: &[T], fs: T, nw: f64, tapers: usize, fft_len: usize, weighting: TaperWeighting
+) -> Multitaper<T> {
+    let len = signal.len();
+    assert!(len > 0, "a power spectrum needs at least one sample");
+    assert!(tapers > 0, "a multitaper estimate needs at least one taper");
+    assert!(fft_len >= len, "fft length {} is shorter than the signal's {}", fft_len, len);
+    let (windows, concentrations) = dpss::<T>(len, nw, tapers);
+    let bins = fft_len / 2 + 1;
+
+    // the tapered spectra, one row per taper
+    let mut spectra = vec![zero(); tapers * fft_len];
+    for (row, window) in spectra.chunks_exact_mut(fft_len).zip(windows.iter()) {
+        for ((y, x), w) in row.iter_mut().zip(signal.iter()).zip(window.iter()) {
+            *y = Complex::new(*x * *w, T::zero());
+        }
+    }
+    crate::batch(&mut spectra, fft_len, tapers, Direction::Forward);
+    let column = |k: usize| spectra.iter().skip(k).step_by(fft_len);
+
+    // the adaptive weights allow for leakage of up to the signal's whole
+    // power into every bin, spread evenly over the band as for white noise
+    let broadband = signal.iter().map(|x| *x * *x).sum::<T>() / (T::from_usize(len) * fs);
+    let two = T::from_f64(2.0);
+    let mut psd = Vec::with_capacity(bins);
+    let mut degrees_of_freedom = Vec::with_capacity(bins);
+    let mut f_statistic = Vec::with_capacity(bins);
+    let mut p_values = Vec::with_capacity(bins);
+    let sums: Vec<T> = windows.iter().map(|w| w.iter().copied().sum()).collect();
+    let sum_squares: T = sums.iter().map(|u| *u * *u).sum();
+    for k in 0..bins {
+        let eigenspectra: Vec<T> = column(k).map(|y| y.norm_sqr() / fs).collect();
+        let (estimate, dof) = match weighting {
+            TaperWeighting::Equal => (
+                eigenspectra.iter().copied().sum::<T>() / T::from_usize(tapers),
+                T::from_usize(2 * tapers),
+            ),
+            TaperWeighting::Adaptive => adapt(&eigenspectra, &concentrations, broadband),
+        };
+        psd.push(if folded(k, fft_len) { estimate * two } else { estimate });
+        degrees_of_freedom.push(dof);
+
+        // regress the tapered spectra on the tapers' own DC responses: a
+        // sinusoid at this frequency makes each Y_k proportional to U_k
+        let (f, p) = if tapers < 2 {
+            (T::nan(), T::nan())
+        } else {
+            let amplitude = column(k).zip(sums.iter()).map(|(y, u)| y * *u).sum::<Complex<T>>()
+                / sum_squares;
+            let residual: T = column(k).zip(sums.iter()).map(|(y, u)| (y - amplitude * *u).norm_sqr()).sum();
+            let degrees = T::from_usize(tapers - 1);
+            let f = degrees * amplitude.norm_sqr() * sum_squares / residual;
+            (f, (T::one() + f / degrees).powf(-degrees))
+        };
+        f_statistic.push(f);
+        p_values.push(p);
+    }
+
+    Multitaper {
+        frequencies: crate::rfftfreq(fft_len, T::one() / fs),
+        psd,
+        degrees_of_freedom,
+        f_statistic,
+        p_values,
+    }
+}
+
+/// Thomson's adaptive combination of one bin's eigenspectra (Percival and
+/// Walden, 1993, eqs. 369a-370b), and its degrees of freedom
+fn adapt<T: FftFloat>(eigenspectra: &[T], concentrations: &[T], broadband: T) -> (T, T) {
+    let two = T::from_f64(2.0);
+    if broadband == T::zero() {
+        // a signal of zeros
+        return (T::zero(), T::from_usize(2 * eigenspectra.len()));
+    }
+    let mut estimate = eigenspectra.iter().take(2).copied().sum::<T>()
+        / T::from_usize(eigenspectra.len().min(2));
+    let mut weights = vec![T::one(); eigenspectra.len()];
+    for _ in 0..100 {
+        for (d, lambda) in weights.iter_mut().zip(concentrations.iter()) {
+            *d = lambda.sqrt() * estimate / (*lambda * estimate + (T::one() - *lambda) * broadband);
+        }
+        let total: T = weights.iter().map(|d| *d * *d).sum();
+        if total == T::zero() {
+            // nothing at all in this bin
+            return (T::zero(), T::from_usize(2 * eigenspectra.len()));
+        }
+        let next = weights.iter().zip(eigenspectra.iter()).map(|(d, s)| *d * *d * *s).sum::<T>() / total;
+        let converged = (next - estimate).abs() <= T::from_f64(1e-10) * next;
+        estimate = next;
+        if converged {
+            break;
+        }
+    }
+    let squares: T = weights.iter().map(|d| *d * *d).sum();
+    let fourths: T = weights.iter().map(|d| (*d * *d) * (*d * *d)).sum();
+    (estimate, two * squares * squares / fourths)
+}
+
+/// the `index`th smallest eigenvalue of a symmetric tridiagonal matrix, by
+/// bisection on Sturm sequence counts
+fn eigenvalue(diagonal: &[f64], off_diagonal: &[f64], index: usize) -> f64 {
+    // Gershgorin's discs bound every eigenvalue
+    let radius = |n: usize| {
+        let before = if n > 0 { off_diagonal[n - 1].abs() } else { 0.0 };
+        before + off_diagonal.get(n).map_or(0.0, |e| e.abs())
+    };
+    let mut low = (0..diagonal.len()).map(|n| diagonal[n] - radius(n)).fold(f64::INFINITY, f64::min);
+    let mut high = (0..diagonal.len()).map(|n| diagonal[n] + radius(n)).fold(f64::NEG_INFINITY, f64::max);
+    let scale = low.abs().max(high.abs()).max(f64::MIN_POSITIVE);
+    loop {
+        let middle = low + (high - low) / 2.0;
+        if middle <= low || middle >= high {
+            return middle;
+        }
+        // how many eigenvalues lie below `middle`: the number of negative
+        // pivots in the LDL^T factorisation of the matrix minus middle
+        let mut below = 0;
+        let mut pivot = 1.0;
+        for (n, d) in diagonal.iter().enumerate() {
+            let coupling = if n > 0 { off_diagonal[n - 1] * off_diagonal[n - 1] / pivot } else { 0.0 };
+            pivot = d - middle - coupling;
+            if pivot == 0.0 {
+                pivot = -f64::EPSILON * scale;
+            }
+            if pivot < 0.0 {
+                below += 1;
+            }
+        }
+        if below > index {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+}
+
+/// the unit eigenvector of a symmetric tridiagonal matrix for `eigenvalue`,
+/// by inverse iteration, kept orthogonal to the eigenvectors in `found`
+fn eigenvector(diagonal: &[f64], off_diagonal: &[f64], eigenvalue: f64, found: &[Vec<f64>]) -> Vec<f64> {
+    let len = diagonal.len();
+    let shifted: Vec<f64> = diagonal.iter().map(|d| d - eigenvalue).collect();
+    // any start with a component along the eigenvector will do; this one is
+    // neither symmetric nor antisymmetric
+    let mut vector: Vec<f64> = (0..len).map(|n| 1.0 + n as f64 / len as f64).collect();
+    for _ in 0..3 {
+        vector = solve_tridiagonal(off_diagonal, &shifted, off_diagonal, &vector);
+        for other in found {
+            let projection: f64 = vector.iter().zip(other.iter()).map(|(a, b)| a * b).sum();
+            vector.iter_mut().zip(other.iter()).for_each(|(a, b)| *a -= projection * b);
+        }
+        let norm = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+        vector.iter_mut().for_each(|x| *x /= norm);
+    }
+    vector
+}
+
+/// solve A x = b for tridiagonal A with `lower`, `diagonal` and `upper`
+/// diagonals, by Gaussian elimination with partial pivoting; a zero pivot
+/// is nudged off zero, since inverse iteration solves nearly singular systems
+/// on purpose
+fn solve_tridiagonal(lower: &[f64], diagonal: &[f64], upper: &[f64], b: &[f64]) -> Vec<f64> {
+    let len = diagonal.len();
+    let tiny = f64::EPSILON * diagonal.iter().chain(upper.iter()).fold(f64::MIN_POSITIVE, |m, x| m.max(x.abs()));
+    let nudge = |pivot: f64| if pivot == 0.0 { tiny } else { pivot };
+
+    // each row of the upper triangular factor has up to three entries, on
+    // the diagonal and the two after it
+    let mut rows = vec![[0.0; 3]; len];
+    let mut rhs = vec![0.0; len];
+    let mut pivot = [diagonal[0], upper.first().copied().unwrap_or(0.0), 0.0];
+    let mut pivot_rhs = b[0];
+    for n in 0..len - 1 {
+        let mut next = [lower[n], diagonal[n + 1], upper.get(n + 1).copied().unwrap_or(0.0)];
+        let mut next_rhs = b[n + 1];
+        if next[0].abs() > pivot[0].abs() {
+            std::mem::swap(&mut pivot, &mut next);
+            std::mem::swap(&mut pivot_rhs, &mut next_rhs);
+        }
+        pivot[0] = nudge(pivot[0]);
+        let factor = next[0] / pivot[0];
+        rows[n] = pivot;
+        rhs[n] = pivot_rhs;
+        pivot = [next[1] - factor * pivot[1], next[2] - factor * pivot[2], 0.0];
+        pivot_rhs = next_rhs - factor * pivot_rhs;
+    }
+    pivot[0] = nudge(pivot[0]);
+    rows[len - 1] = pivot;
+    rhs[len - 1] = pivot_rhs;
+
+    let mut x = vec![0.0; len];
+    for n in (0..len).rev() {
+        let after = |i: usize| x.get(n + i).copied().unwrap_or(0.0);
+        x[n] = (rhs[n] - rows[n][1] * after(1) - rows[n][2] * after(2)) / rows[n][0];
+    }
+    x
+}
+
+/// the fraction of each taper's energy within `w` cycles per sample of DC:
+/// the quadratic form of the taper with the sinc kernel, summed over lags
+/// of its autocorrelation, which comes from the fft
+fn concentrations(tapers: &[Vec<f64>], w: f64) -> Vec<f64> {
+    let (Some(first), count) = (tapers.first(), tapers.len()) else {
+        return Vec::new();
+    };
+    let len = first.len();
+    let padded = 2 * len;
+    let mut spectra = vec![zero::<f64>(); count * padded];
+    for (row, taper) in spectra.chunks_exact_mut(padded).zip(tapers.iter()) {
+        row.iter_mut().zip(taper.iter()).for_each(|(y, x)| *y = Complex::new(*x, 0.0));
+    }
+    crate::batch(&mut spectra, padded, count, Direction::Forward);
+    spectra.iter_mut().for_each(|y| *y = Complex::new(y.norm_sqr(), 0.0));
+    crate::batch(&mut spectra, padded, count, Direction::Inverse);
+    spectra.chunks_exact(padded).map(|autocorrelation| {
+        let tail: f64 = (1..len).map(|lag| {
+            autocorrelation[lag].re * (2.0 * PI * w * lag as f64).sin() / (PI * lag as f64)
+        }).sum();
+        2.0 * w * autocorrelation[0].re + 2.0 * tail
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::noise;
+
+    #[test]
+    fn dpss_orthonormal_test() {
+        let (tapers, _) = dpss::<f64>(256, 4.0, 8);
+        for (j, a) in tapers.iter().enumerate() {
+            for (k, b) in tapers.iter().enumerate() {
+                let dot: f64 = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum();
+                let expected = if j == k { 1.0 } else { 0.0 };
+                assert!((dot - expected).abs() < 1e-10, "tapers {} and {} give {}", j, k, dot);
+            }
+        }
+        for (k, taper) in tapers.iter().enumerate() {
+            // even orders are symmetric with a positive sum, odd ones
+            // antisymmetric and starting positive
+            let parity = if k % 2 == 0 { 1.0 } else { -1.0 };
+            for (a, b) in taper.iter().zip(taper.iter().rev()) {
+                assert!((a - parity * b).abs() < 1e-10);
+            }
+            if k % 2 == 0 {
+                assert!(taper.iter().sum::<f64>() > 0.0);
+            } else {
+                assert!(taper[1] > 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn dpss_concentration_test() {
+        // each taper is an eigenvector of the sinc kernel, with its
+        // concentration as the eigenvalue
+        let (len, nw) = (100, 3.0);
+        let w = nw / len as f64;
+        let (tapers, concentrations) = dpss::<f64>(len, nw, 7);
+        let kernel = |lag: f64| if lag == 0.0 { 2.0 * w } else { (2.0 * PI * w * lag).sin() / (PI * lag) };
+        for (taper, lambda) in tapers.iter().zip(concentrations.iter()) {
+            for n in 0..len {
+                let filtered: f64 = (0..len).map(|m| kernel(n as f64 - m as f64) * taper[m]).sum();
+                assert!((filtered - lambda * taper[n]).abs() < 1e-10);
+            }
+        }
+        assert!(concentrations.windows(2).all(|c| c[0] > c[1]));
+        assert!(concentrations[..4].iter().all(|c| *c > 0.99));
+        assert!(concentrations[6] < 0.5);
+    }
+
+    #[test]
+    fn dpss_long_test() {
+        // inverse iteration still separates the tapers when their
+        // concentrations agree to many places
+        let (tapers, concentrations) = dpss::<f64>(4096, 4.0, 4);
+        let dot: f64 = tapers[0].iter().zip(tapers[2].iter()).map(|(a, b)| a * b).sum();
+        assert!(dot.abs() < 1e-10);
+        assert!(concentrations.iter().all(|c| (c - 1.0).abs() < 1e-4));
+    }
+
+    #[test]
+    fn multitaper_noise_density_test() {
+        // unit-variance white noise at 10 Hz has a one-sided density of 0.2
+        let signal = noise(8192, 21);
+        for weighting in [TaperWeighting::Equal, TaperWeighting::Adaptive] {
+            let estimate = multitaper_psd(&signal, 10.0, 4.0, 7, 8192, weighting);
+            let psd = &estimate.psd[1..4096];
+            let mean = psd.iter().sum::<f64>() / psd.len() as f64;
+            assert!((mean - 0.2).abs() < 0.005, "{:?} gives {}", weighting, mean);
+            let dof = &estimate.degrees_of_freedom[1..4096];
+            assert!(dof.iter().all(|d| *d > 13.0 && *d <= 14.0 + 1e-9));
+        }
+    }
+
+    #[test]
+    fn f_test_test() {
+        // a weak line off the bins, buried in the noise of a periodogram,
+        // on a grid four times finer than the bins
+        let (len, f0) = (4096, 0.1234);
+        let disturbance = noise(len, 4);
+        let signal: Vec<f64> = (0..len).map(
+            |n| 0.5 * (2.0 * PI * f0 * n as f64).cos() + disturbance[n]
+        ).collect();
+        let estimate = multitaper_psd(&signal, 1.0, 4.0, 7, 4 * len, TaperWeighting::Adaptive);
+        let lines = estimate.lines(1e-5);
+        assert_eq!(lines.len(), 1);
+        assert!((lines[0] - f0).abs() < 1.0 / len as f64);
+
+        // away from the line the p-values are uniform
+        let far: Vec<f64> = estimate.p_values.iter().zip(estimate.frequencies.iter())
+            .filter(|(_, f)| (*f - f0).abs() > 8.0 / len as f64)
+            .map(|(p, _)| *p).collect();
+        let alarms = far.iter().filter(|p| **p < 0.05).count() as f64 / far.len() as f64;
+        assert!((alarms - 0.05).abs() < 0.02, "{} false alarms", alarms);
+    }
+
+    #[test]
+    fn adaptive_leakage_test() {
+        // a strong line over a weak floor: the last taper leaks the line
+        // across the band, and adaptive weighting leaves it out
+        let len = 1024;
+        let floor = noise(len, 12);
+        let signal: Vec<f64> = (0..len).map(
+            |n| (2.0 * PI * 0.2 * n as f64).sin() + 1e-5 * floor[n]
+        ).collect();
+        let equal = multitaper_psd(&signal, 1.0, 4.0, 8, len, TaperWeighting::Equal);
+        let adaptive = multitaper_psd(&signal, 1.0, 4.0, 8, len, TaperWeighting::Adaptive);
+        let far = |psd: &[f64]| psd[400..500].iter().sum::<f64>() / 100.0;
+        assert!(far(&adaptive.psd) < 0.01 * far(&equal.psd));
+        // a floor of 2e-10 / fs
+        assert!(far(&adaptive.psd) < 1e-9);
+    }
+
+    #[test]
+    fn multitaper_f32_test() {
+        let signal: Vec<f32> = noise(512, 3).iter().map(|x| *x as f32).collect();
+        let estimate = multitaper_psd(&signal, 1.0, 3.0, 5, 1000, TaperWeighting::Adaptive);
+        assert_eq!(estimate.psd.len(), 501);
+        assert!(estimate.psd.iter().all(|p| p.is_finite() && *p > 0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn dpss_rejects_wide_band_test() {
+        dpss::<f64>(16, 8.0, 2);
+    }
+}
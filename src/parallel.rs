@@ -0,0 +1,143 @@
+//! splitting transforms across threads
+//!
+//! the two halves of a radix-2 Cooley-Tukey step are independent, so the top
+//! levels of the recursion can run on separate threads. every thread does
+//! exactly the arithmetic the serial path would, in the same order, so the
+//! output is bit-identical no matter how many threads are used.
+
+use std::thread;
+
+/// how many threads a transform may use, and how long it has to be before
+/// it's worth using them
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::Parallelism;
+///
+/// let signal: Vec<_> = (0..4096).map(|x| Complex::new((x % 7) as f64, 0f64)).collect();
+/// let parallelism = Parallelism { threads: 4, min_len: 1024 };
+/// assert_eq!(
+///     fft_rust::fft_parallel(&mut signal.clone(), parallelism),
+///     fft_rust::fft_parallel(&mut signal.clone(), Parallelism::serial())
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parallelism {
+    /// the most threads a single transform runs on, including the caller's
+    pub threads: usize,
+    /// sub-transforms shorter than this stay on one thread
+    pub min_len: usize,
+}
+
+impl Parallelism {
+    /// never spawn threads
+    pub fn serial() -> Parallelism {
+        Parallelism { threads: 1, min_len: usize::MAX }
+    }
+
+    /// true if a sub-transform of length `len` should be split across threads
+    pub(crate) fn splits(&self, len: usize) -> bool {
+        self.threads > 1 && len >= self.min_len
+    }
+
+    /// the budgets for the two halves of a split: the spawned thread takes
+    /// half the threads and the current one keeps the rest
+    pub(crate) fn halves(&self) -> (Parallelism, Parallelism) {
+        let spawned = self.threads / 2;
+        (
+            Parallelism { threads: self.threads - spawned, ..*self },
+            Parallelism { threads: spawned, ..*self },
+        )
+    }
+}
+
+impl Default for Parallelism {
+    /// one thread per core, for transforms of at least 2^14 samples
+    fn default() -> Parallelism {
+        Parallelism {
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            min_len: 1 << 14,
+        }
+    }
+}
+
+/// run `a` and `b`, on two threads if `parallel` is set, and return both results
+pub(crate) fn join<A, B, RA, RB>(parallel: bool, a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    if !parallel {
+        return (a(), b());
+    }
+    thread::scope(|scope| {
+        let b = scope.spawn(b);
+        let a = a();
+        (a, b.join().expect("fft worker thread panicked"))
+    })
+}
+
+/// split `data` into up to `threads` runs of whole `chunk_len` chunks and
+/// call `f` on each run, one run per thread
+pub(crate) fn for_each_run<T, F>(data: &mut [T], chunk_len: usize, threads: usize, f: F)
+where
+    T: Send,
+    F: Fn(&mut [T]) + Sync,
+{
+    let chunks = data.len() / chunk_len;
+    let run_len = chunks.div_ceil(threads.max(1)).max(1) * chunk_len;
+    thread::scope(|scope| {
+        let mut runs = data.chunks_mut(run_len);
+        let first = runs.next();
+        for run in runs {
+            let f = &f;
+            scope.spawn(move || f(run));
+        }
+        if let Some(run) = first {
+            f(run);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halves_test() {
+        let parallelism = Parallelism { threads: 5, min_len: 8 };
+        let (a, b) = parallelism.halves();
+        assert_eq!((a.threads, b.threads), (3, 2));
+        assert_eq!((a.min_len, b.min_len), (8, 8));
+        assert!(parallelism.splits(8));
+        assert!(!parallelism.splits(7));
+        assert!(!a.halves().1.halves().1.splits(8));
+        assert!(!Parallelism::serial().splits(usize::MAX));
+    }
+
+    #[test]
+    fn for_each_run_test() {
+        // 4 chunks over 3 threads is 2 runs of 2 chunks
+        for (threads, expected) in [(1, 1), (2, 2), (3, 2), (8, 4)] {
+            let data = &mut [0usize; 12];
+            for_each_run(data, 3, threads, |run| {
+                assert!(run.len().is_multiple_of(3));
+                let first = run.as_ptr() as usize;
+                run.iter_mut().for_each(|x| *x = first);
+            });
+            let runs = data.windows(2).filter(|pair| pair[0] != pair[1]).count() + 1;
+            assert_eq!(runs, expected);
+        }
+    }
+
+    #[test]
+    fn join_test() {
+        for parallel in [false, true] {
+            assert_eq!(join(parallel, || 1 + 1, || "two"), (2, "two"));
+        }
+    }
+}
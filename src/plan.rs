@@ -0,0 +1,727 @@
+//! reusable fft plans
+//!
+//! a plan is built once for a transform length and direction; it keeps the
+//! twiddle factors (the omega values `butterfly` recomputes on every call) and
+//! a scratch buffer around so repeated transforms of the same length don't
+//! allocate or call `exp` again.
+
+use num::complex::Complex;
+
+use crate::bluestein::Bluestein;
+use crate::error::FftError;
+use crate::float::{twiddle, zero, FftFloat};
+use crate::mixed_radix::MixedRadix;
+use crate::parallel::{self, Parallelism};
+use crate::rader::{is_prime, prime_factors, Rader};
+use crate::simd;
+
+/// which way a plan transforms its input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// time domain -> frequency domain, same as `fft`
+    Forward,
+    /// frequency domain -> time domain, same as `ifft` (scaled by 1/N)
+    Inverse,
+}
+
+/// how the forward and inverse transforms are scaled, named after which
+/// direction carries the 1/N (same names as NumPy's `norm` argument)
+///
+/// the forward/inverse pair always round-trips, except with `None`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Normalization {
+    /// forward unscaled, inverse scaled by 1/N; what `fft`/`ifft` and MATLAB do
+    #[default]
+    Backward,
+    /// both directions scaled by 1/sqrt(N), so the transform is unitary and
+    /// preserves energy
+    Ortho,
+    /// forward scaled by 1/N, inverse unscaled; the forward output reads
+    /// directly as amplitudes
+    Forward,
+    /// neither direction scaled
+    None,
+}
+
+impl Normalization {
+    /// the factor a transform of length `len` in `direction` is multiplied by
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fft_rust::{Direction, Normalization};
+    ///
+    /// assert_eq!(Normalization::Backward.factor(4, Direction::Inverse), 0.25);
+    /// assert_eq!(Normalization::Ortho.factor(4, Direction::Forward), 0.5);
+    /// assert_eq!(Normalization::Forward.factor(4, Direction::Inverse), 1.0);
+    /// ```
+    pub fn factor(&self, len: usize, direction: Direction) -> f64 {
+        let n = len as f64;
+        match (self, direction) {
+            (Normalization::Backward, Direction::Inverse) => 1.0 / n,
+            (Normalization::Forward, Direction::Forward) => 1.0 / n,
+            (Normalization::Ortho, _) => 1.0 / n.sqrt(),
+            _ => 1.0,
+        }
+    }
+}
+
+/// how a plan walks the Cooley-Tukey decomposition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// split into even/odd halves recursively, like `fft`; uses a scratch buffer
+    Recursive,
+    /// bit-reverse the input then run log2(N) butterfly passes in place
+    Iterative,
+}
+
+/// a Cooley-Tukey fft for one length and direction
+///
+/// the plan picks a decomposition for the length, and never pads:
+/// - powers of two run the radix-2 kernel directly
+/// - other composite lengths (480, 1000, 3072, ...) are factored into
+///   radix-2, 3, 4 and 5 butterflies plus any larger prime factors
+/// - primes above 5 use Rader's algorithm when p - 1 only has factors of
+///   2, 3 and 5, and Bluestein's chirp-z algorithm otherwise
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+/// use fft_rust::{Direction, FftPlan};
+///
+/// let mut plan = FftPlan::new(4, Direction::Forward);
+/// let data = &mut vec![
+///     Complex::new(4f64, 0f64), Complex::new(3f64, 0f64),
+///     Complex::new(-5f64, 0f64), Complex::new(1f64, 0f64)
+/// ];
+/// plan.process(data);
+/// assert_eq!(fft_rust::approximate_complex(data), &mut vec![
+///     Complex::new(3f64, 0f64), Complex::new(9f64, -2f64),
+///     Complex::new(-5f64, 0f64), Complex::new(9f64, 2f64)
+/// ]);
+///
+/// let mut inverse = FftPlan::new(4, Direction::Inverse);
+/// inverse.process(data);
+/// assert_eq!(fft_rust::approximate_complex(data), &mut vec![
+///     Complex::new(4f64, 0f64), Complex::new(3f64, 0f64),
+///     Complex::new(-5f64, 0f64), Complex::new(1f64, 0f64)
+/// ]);
+///
+/// // a length-3 plan gives 3 bins, not 4
+/// let mut plan = FftPlan::new(3, Direction::Forward);
+/// let data = &mut vec![
+///     Complex::new(1f64, 0f64), Complex::new(1f64, 0f64), Complex::new(1f64, 0f64)
+/// ];
+/// plan.process(data);
+/// assert_eq!(fft_rust::approximate_complex(data), &mut vec![
+///     Complex::new(3f64, 0f64), Complex::new(0f64, 0f64), Complex::new(0f64, 0f64)
+/// ]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FftPlan<T: FftFloat> {
+    len: usize,
+    direction: Direction,
+    algorithm: Algorithm,
+    normalization: Normalization,
+    parallelism: Parallelism,
+    // what the kernel's output (always Backward scaled) is multiplied by
+    // to get `normalization`; None when nothing needs doing
+    rescale: Option<T>,
+    kernel: Kernel<T>,
+    // where `process_strided` collects a strided signal; empty until first used
+    gathered: Vec<Complex<T>>,
+}
+
+#[derive(Debug, Clone)]
+enum Kernel<T: FftFloat> {
+    Radix2 {
+        // e^(sign * 2i * pi * k / len) for k in 0..len/2; a sub-transform of
+        // length len/s uses every s-th entry
+        twiddles: Vec<Complex<T>>,
+        scratch: Vec<Complex<T>>,
+    },
+    MixedRadix(Box<MixedRadix<T>>),
+    Rader(Box<Rader<T>>),
+    Bluestein(Box<Bluestein<T>>),
+}
+
+impl<T: FftFloat> FftPlan<T> {
+    /// build a plan for transforms of length `len` in the given direction
+    /// using the recursive algorithm
+    ///
+    /// panics if `len` is zero
+    pub fn new(len: usize, direction: Direction) -> FftPlan<T> {
+        FftPlan::with_algorithm(len, direction, Algorithm::Recursive)
+    }
+
+    /// build a plan for transforms of length `len` in the given direction
+    /// using the given algorithm for the power-of-two kernel
+    ///
+    /// panics if `len` is zero
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use num::complex::Complex;
+    /// use fft_rust::{Algorithm, Direction, FftPlan};
+    ///
+    /// let mut plan = FftPlan::with_algorithm(2, Direction::Forward, Algorithm::Iterative);
+    /// let data = &mut [Complex::new(1f64, 0f64), Complex::new(3f64, 0f64)];
+    /// plan.process(data);
+    /// assert_eq!(data, &[Complex::new(4f64, 0f64), Complex::new(-2f64, 0f64)]);
+    /// ```
+    pub fn with_algorithm(
+        len: usize, direction: Direction, algorithm: Algorithm
+    ) -> FftPlan<T> {
+        assert!(len > 0, "FftPlan length must be greater than zero");
+        FftPlan {
+            len,
+            direction,
+            algorithm,
+            normalization: Normalization::Backward,
+            parallelism: Parallelism::serial(),
+            rescale: None,
+            kernel: Kernel::new(len, direction, algorithm),
+            gathered: vec![],
+        }
+    }
+
+    /// change how this plan scales its output; plans start out `Backward`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use num::complex::Complex;
+    /// use fft_rust::{Direction, FftPlan, Normalization};
+    ///
+    /// let mut plan = FftPlan::new(4, Direction::Forward);
+    /// plan.set_normalization(Normalization::Forward);
+    /// let data = &mut [Complex::new(1f64, 0f64); 4];
+    /// plan.process(data);
+    /// assert_eq!(data[0], Complex::new(1f64, 0f64));
+    /// ```
+    pub fn set_normalization(&mut self, normalization: Normalization) {
+        let wanted = normalization.factor(self.len, self.direction);
+        let done = Normalization::Backward.factor(self.len, self.direction);
+        self.normalization = normalization;
+        self.rescale = if normalization == Normalization::Backward {
+            None
+        } else {
+            Some(T::from_f64(wanted / done))
+        };
+    }
+
+    /// how this plan scales its output
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// let the recursive power-of-two kernel split its top levels across
+    /// threads; plans start out serial. the output is bit-identical either way
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use num::complex::Complex;
+    /// use fft_rust::{Direction, FftPlan, Parallelism};
+    ///
+    /// let mut plan = FftPlan::new(1 << 16, Direction::Forward);
+    /// plan.set_parallelism(Parallelism { threads: 4, min_len: 1 << 12 });
+    /// let data = &mut vec![Complex::new(1f64, 0f64); 1 << 16];
+    /// plan.process(data);
+    /// assert_eq!(data[0], Complex::new(65536f64, 0f64));
+    /// ```
+    pub fn set_parallelism(&mut self, parallelism: Parallelism) {
+        self.parallelism = parallelism;
+    }
+
+    /// how many threads this plan may use
+    pub fn parallelism(&self) -> Parallelism {
+        self.parallelism
+    }
+
+    /// the transform length this plan was built for; never zero, since
+    /// plans can't be built for empty input
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// the direction this plan was built for
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// the algorithm this plan was built with
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// transform `data` in place, or return `FftError::MismatchedShapes` if
+    /// `data` isn't the plan's length
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use num::complex::Complex;
+    /// use fft_rust::{Direction, FftError, FftPlan};
+    ///
+    /// let mut plan = FftPlan::new(4, Direction::Forward);
+    /// let data = &mut [Complex::new(1f64, 0f64); 3];
+    /// assert!(matches!(
+    ///     plan.try_process(data),
+    ///     Err(FftError::MismatchedShapes { expected: 4, found: 3 })
+    /// ));
+    /// ```
+    pub fn try_process(&mut self, data: &mut [Complex<T>]) -> Result<(), FftError> {
+        if data.len() != self.len {
+            return Err(FftError::MismatchedShapes { expected: self.len, found: data.len() });
+        }
+        self.process(data);
+        Ok(())
+    }
+
+    /// transform `data` as back-to-back signals of the plan's length, reusing
+    /// this plan (and its scratch buffer) for every one of them
+    ///
+    /// with `set_parallelism`, batches of at least `min_len` samples in total
+    /// are spread across threads a whole signal at a time, each thread working
+    /// with its own copy of the plan
+    ///
+    /// panics if `data.len()` is not a multiple of the plan's length
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use num::complex::Complex;
+    /// use fft_rust::{Direction, FftPlan};
+    ///
+    /// let mut plan = FftPlan::new(2, Direction::Forward);
+    /// let data = &mut [
+    ///     Complex::new(1f64, 0f64), Complex::new(3f64, 0f64),
+    ///     Complex::new(2f64, 0f64), Complex::new(2f64, 0f64)
+    /// ];
+    /// plan.process_batch(data);
+    /// assert_eq!(data, &[
+    ///     Complex::new(4f64, 0f64), Complex::new(-2f64, 0f64),
+    ///     Complex::new(4f64, 0f64), Complex::new(0f64, 0f64)
+    /// ]);
+    /// ```
+    pub fn process_batch(&mut self, data: &mut [Complex<T>]) {
+        assert!(
+            data.len().is_multiple_of(self.len),
+            "FftPlan built for length {} was given {} samples, which isn't a whole number of signals",
+            self.len, data.len()
+        );
+        let count = data.len() / self.len;
+        if count < 2 || !self.parallelism.splits(data.len()) {
+            for signal in data.chunks_exact_mut(self.len) {
+                self.process(signal);
+            }
+            return;
+        }
+
+        let mut worker = self.clone();
+        worker.set_parallelism(Parallelism::serial());
+        parallel::for_each_run(data, self.len, self.parallelism.threads, |run| {
+            worker.clone().process_batch(run)
+        });
+    }
+
+    /// transform the signal `data[offset]`, `data[offset + stride]`, ... (as
+    /// many samples as the plan's length) in place, leaving every other sample
+    /// alone; this transforms an image column, one channel of interleaved
+    /// audio or one plane of interleaved RGB without copying it out first
+    ///
+    /// panics if `stride` is zero or the signal runs past the end of `data`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use num::complex::Complex;
+    /// use fft_rust::{Direction, FftPlan};
+    ///
+    /// // interleaved stereo: left, right, left, right
+    /// let data = &mut [
+    ///     Complex::new(1f64, 0f64), Complex::new(5f64, 0f64),
+    ///     Complex::new(3f64, 0f64), Complex::new(5f64, 0f64)
+    /// ];
+    /// let mut plan = FftPlan::new(2, Direction::Forward);
+    /// plan.process_strided(data, 0, 2);
+    /// assert_eq!(data, &[
+    ///     Complex::new(4f64, 0f64), Complex::new(5f64, 0f64),
+    ///     Complex::new(-2f64, 0f64), Complex::new(5f64, 0f64)
+    /// ]);
+    /// ```
+    pub fn process_strided(&mut self, data: &mut [Complex<T>], offset: usize, stride: usize) {
+        assert!(stride > 0, "stride must be greater than zero");
+        let last = (self.len - 1).checked_mul(stride).and_then(|x| x.checked_add(offset));
+        assert!(
+            last.is_some_and(|last| last < data.len()),
+            "a length {} signal at offset {} with stride {} doesn't fit in {} samples",
+            self.len, offset, stride, data.len()
+        );
+        if stride == 1 {
+            return self.process(&mut data[offset..offset + self.len]);
+        }
+
+        let mut gathered = std::mem::take(&mut self.gathered);
+        gathered.clear();
+        gathered.extend(data[offset..].iter().step_by(stride).take(self.len));
+        self.process(&mut gathered);
+        for (x, y) in data[offset..].iter_mut().step_by(stride).zip(gathered.iter()) {
+            *x = *y;
+        }
+        self.gathered = gathered;
+    }
+
+    /// transform `data` in place
+    ///
+    /// panics if `data.len()` is not the plan's length
+    pub fn process(&mut self, data: &mut [Complex<T>]) {
+        assert_eq!(
+            data.len(), self.len,
+            "FftPlan built for length {} was given {} samples", self.len, data.len()
+        );
+        let inverse = self.direction == Direction::Inverse;
+        match &mut self.kernel {
+            Kernel::Radix2 { twiddles, scratch } => match self.algorithm {
+                Algorithm::Recursive => {
+                    scratch.copy_from_slice(data);
+                    recursive_fft(
+                        scratch, 1, data, twiddles, 1, inverse, self.parallelism
+                    );
+                }
+                Algorithm::Iterative => iterative_fft(data, twiddles, inverse),
+            },
+            Kernel::MixedRadix(mixed_radix) => mixed_radix.process(data),
+            Kernel::Rader(rader) => rader.process(data),
+            Kernel::Bluestein(bluestein) => bluestein.process(data),
+        }
+        if let Some(rescale) = self.rescale {
+            for x in data.iter_mut() {
+                *x *= rescale;
+            }
+        }
+    }
+}
+
+impl<T: FftFloat> Kernel<T> {
+    /// pick the cheapest exact decomposition for `len`
+    fn new(len: usize, direction: Direction, algorithm: Algorithm) -> Kernel<T> {
+        if crate::is_int_power_of_two(len) {
+            let scratch_len = match algorithm {
+                Algorithm::Recursive => len,
+                Algorithm::Iterative => 0,
+            };
+            Kernel::Radix2 {
+                twiddles: twiddles(len, direction),
+                scratch: vec![zero(); scratch_len],
+            }
+        } else if len > 5 && is_prime(len) {
+            if prime_factors(len - 1).iter().all(|f| *f <= 5) {
+                Kernel::Rader(Box::new(Rader::new(len, direction, algorithm)))
+            } else {
+                Kernel::Bluestein(Box::new(Bluestein::new(len, direction, algorithm)))
+            }
+        } else {
+            Kernel::MixedRadix(Box::new(MixedRadix::new(len, direction, algorithm)))
+        }
+    }
+}
+
+/// the first len/2 powers of the primitive len-th root of unity for `direction`
+pub(crate) fn twiddles<T: FftFloat>(len: usize, direction: Direction) -> Vec<Complex<T>> {
+    let sign = match direction {
+        Direction::Forward => -1f64,
+        Direction::Inverse => 1f64,
+    };
+    (0..len / 2).map(|k| twiddle(k, len, sign)).collect()
+}
+
+/// decimation-in-time fft of every `stride`-th element of `input` into `output`
+///
+/// the even samples land in the lower half of `output` and the odd samples in
+/// the upper half, then the halves are combined in place with the butterfly.
+/// outputs of at least `parallelism.min_len` samples do the halves on two threads
+fn recursive_fft<T: FftFloat>(
+    input: &[Complex<T>], stride: usize, output: &mut [Complex<T>],
+    twiddles: &[Complex<T>], twiddle_stride: usize, inverse: bool,
+    parallelism: Parallelism
+) {
+    let n = output.len();
+    if n == 1 {
+        output[0] = input[0];
+        return;
+    }
+
+    let n_over_two = n / 2;
+    let (y_e, y_o) = output.split_at_mut(n_over_two);
+    let (even, odd) = parallelism.halves();
+    parallel::join(
+        parallelism.splits(n),
+        || recursive_fft(input, stride * 2, y_e, twiddles, twiddle_stride * 2, inverse, even),
+        || recursive_fft(
+            &input[stride..], stride * 2, y_o, twiddles, twiddle_stride * 2, inverse, odd
+        ),
+    );
+
+    simd::radix2(y_e, y_o, twiddles, twiddle_stride, inverse);
+}
+
+/// in-place radix-2 fft: bit-reversal reorder followed by log2(n) butterfly passes
+///
+/// `twiddles` must come from `twiddles(data.len(), ..)`
+pub(crate) fn iterative_fft<T: FftFloat>(
+    data: &mut [Complex<T>], twiddles: &[Complex<T>], inverse: bool
+) {
+    let n = data.len();
+    bit_reverse_permute(data);
+
+    let mut m = 2;
+    while m <= n {
+        let n_over_two = m / 2;
+        let twiddle_stride = n / m;
+        for block in data.chunks_exact_mut(m) {
+            let (y_e, y_o) = block.split_at_mut(n_over_two);
+            simd::radix2(y_e, y_o, twiddles, twiddle_stride, inverse);
+        }
+        m *= 2;
+    }
+}
+
+/// reorder a power-of-two length slice so element i moves to the index
+/// whose bits are the bits of i reversed
+pub(crate) fn bit_reverse_permute<T>(data: &mut [T]) {
+    let n = data.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{approximate_complex, fft, float_array_to_complex, ifft};
+    use crate::tests::{noise, signal};
+
+    #[test]
+    fn process_matches_fft_test() {
+        for len in [1, 2, 4, 8, 16, 64] {
+            let input = float_array_to_complex(noise(len, 1));
+            let expected = fft(&mut input.clone());
+            let mut data = input.clone();
+            FftPlan::new(len, Direction::Forward).process(&mut data);
+            for (a, b) in data.iter().zip(expected.iter()) {
+                assert!((a - b).norm() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn process_inverse_matches_ifft_test() {
+        let input = float_array_to_complex(vec![3., 9., -5., 9., 1., 0., 2., 4.]);
+        let expected = ifft(&mut input.clone());
+        let mut data = input.clone();
+        FftPlan::new(8, Direction::Inverse).process(&mut data);
+        for (a, b) in data.iter().zip(expected.iter()) {
+            assert!((a - b).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn iterative_matches_recursive_test() {
+        for len in [1, 2, 4, 8, 16, 64, 256] {
+            let input = signal(len, 1);
+            for direction in [Direction::Forward, Direction::Inverse] {
+                let mut recursive = input.clone();
+                let mut iterative = input.clone();
+                FftPlan::with_algorithm(len, direction, Algorithm::Recursive)
+                    .process(&mut recursive);
+                FftPlan::with_algorithm(len, direction, Algorithm::Iterative)
+                    .process(&mut iterative);
+                for (a, b) in recursive.iter().zip(iterative.iter()) {
+                    assert!((a - b).norm() < 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_matches_serial_test() {
+        let input = signal(1 << 12, 1);
+        for direction in [Direction::Forward, Direction::Inverse] {
+            let mut serial = input.clone();
+            FftPlan::new(input.len(), direction).process(&mut serial);
+            for threads in [2, 3, 8] {
+                let mut plan = FftPlan::new(input.len(), direction);
+                plan.set_parallelism(Parallelism { threads, min_len: 64 });
+                let mut data = input.clone();
+                plan.process(&mut data);
+                assert_eq!(data, serial);
+            }
+        }
+    }
+
+    #[test]
+    fn process_batch_test() {
+        let input = signal(12 * 100, 2);
+        let mut expected = input.clone();
+        let mut plan = FftPlan::new(12, Direction::Forward);
+        for signal in expected.chunks_exact_mut(12) {
+            plan.process(signal);
+        }
+
+        let mut serial = input.clone();
+        plan.process_batch(&mut serial);
+        assert_eq!(serial, expected);
+
+        plan.set_parallelism(Parallelism { threads: 3, min_len: 64 });
+        let mut parallel = input.clone();
+        plan.process_batch(&mut parallel);
+        assert_eq!(parallel, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn process_batch_rejects_partial_signal_test() {
+        FftPlan::new(4, Direction::Forward).process_batch(&mut [Complex::new(1f64, 0f64); 6]);
+    }
+
+    #[test]
+    fn process_strided_test() {
+        // a 3 x 4 row-major image; transform each column in place
+        let input = signal(12, 3);
+        let mut data = input.clone();
+        let mut plan = FftPlan::new(3, Direction::Forward);
+        for column in 0..4 {
+            plan.process_strided(&mut data, column, 4);
+        }
+        for column in 0..4 {
+            let signal: Vec<_> = input[column..].iter().step_by(4).copied().collect();
+            let output: Vec<_> = data[column..].iter().step_by(4).copied().collect();
+            assert_eq!(output, crate::fft_exact(&signal));
+        }
+
+        // a stride of 1 is a contiguous slice
+        let mut data = input.clone();
+        plan.process_strided(&mut data, 5, 1);
+        assert_eq!(data[..5], input[..5]);
+        assert_eq!(data[5..8], crate::fft_exact(&input[5..8])[..]);
+        assert_eq!(data[8..], input[8..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn process_strided_rejects_overrun_test() {
+        FftPlan::new(3, Direction::Forward)
+            .process_strided(&mut [Complex::new(1f64, 0f64); 6], 1, 3);
+    }
+
+    #[test]
+    fn bit_reverse_permute_test() {
+        let data = &mut [0, 1, 2, 3, 4, 5, 6, 7];
+        bit_reverse_permute(data);
+        assert_eq!(data, &[0, 4, 2, 6, 1, 5, 3, 7]);
+
+        let data = &mut [0, 1];
+        bit_reverse_permute(data);
+        assert_eq!(data, &[0, 1]);
+    }
+
+    #[test]
+    fn plan_is_reusable_test() {
+        let mut forward = FftPlan::new(4, Direction::Forward);
+        let mut inverse = FftPlan::new(4, Direction::Inverse);
+        for _ in 0..3 {
+            let data = &mut float_array_to_complex(vec![4., 3., -5., 1.]);
+            forward.process(data);
+            inverse.process(data);
+            assert_eq!(
+                approximate_complex(&mut data.to_vec()),
+                &mut float_array_to_complex(vec![4., 3., -5., 1.])
+            );
+        }
+    }
+
+    #[test]
+    fn plan_matches_dft_test() {
+        for len in [3, 6, 7, 11, 23, 47, 480, 1000, 3072] {
+            let input = signal(len, 4);
+            let mut output = input.clone();
+            FftPlan::new(len, Direction::Forward).process(&mut output);
+            for (a, b) in output.iter().zip(crate::tests::dft(&input, false).iter()) {
+                assert!((a - b).norm() < 1e-7, "length {} differs", len);
+            }
+            FftPlan::new(len, Direction::Inverse).process(&mut output);
+            for (a, b) in output.iter().zip(input.iter()) {
+                assert!((a - b).norm() < 1e-9, "length {} does not round trip", len);
+            }
+        }
+    }
+
+    #[test]
+    fn plan_picks_kernel_test() {
+        let kernel = |len| FftPlan::<f64>::new(len, Direction::Forward).kernel;
+        assert!(matches!(kernel(1024), Kernel::Radix2 { .. }));
+        assert!(matches!(kernel(1000), Kernel::MixedRadix(_)));
+        assert!(matches!(kernel(5), Kernel::MixedRadix(_)));
+        assert!(matches!(kernel(31), Kernel::Rader(_)));
+        assert!(matches!(kernel(23), Kernel::Bluestein(_)));
+    }
+
+    #[test]
+    fn normalization_test() {
+        let input = float_array_to_complex(vec![3., 9., -5., 9., 1., 0., 2.]);
+        let energy = |x: &[Complex<f64>]| x.iter().map(|c| c.norm_sqr()).sum::<f64>();
+        let unscaled = crate::tests::dft(&input, false);
+        for norm in [
+            Normalization::Backward, Normalization::Ortho,
+            Normalization::Forward, Normalization::None
+        ] {
+            let mut forward = FftPlan::new(7, Direction::Forward);
+            let mut inverse = FftPlan::new(7, Direction::Inverse);
+            forward.set_normalization(norm);
+            inverse.set_normalization(norm);
+            assert_eq!(forward.normalization(), norm);
+
+            let mut data = input.clone();
+            forward.process(&mut data);
+            let factor = norm.factor(7, Direction::Forward);
+            for (a, b) in data.iter().zip(unscaled.iter()) {
+                assert!((a - b * factor).norm() < 1e-9);
+            }
+            if norm == Normalization::Ortho {
+                assert!((energy(&data) - energy(&input)).abs() < 1e-9);
+            }
+
+            inverse.process(&mut data);
+            let round_trip = if norm == Normalization::None { 7.0 } else { 1.0 };
+            for (a, b) in data.iter().zip(input.iter()) {
+                assert!((a - b * round_trip).norm() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_rejects_empty_test() {
+        FftPlan::<f64>::new(0, Direction::Forward);
+    }
+
+    #[test]
+    #[should_panic]
+    fn process_rejects_wrong_length_test() {
+        FftPlan::new(4, Direction::Forward).process(&mut [Complex::new(1f64, 0f64); 2]);
+    }
+}
@@ -0,0 +1,294 @@
+//! power spectral density estimates
+//!
+//! the periodogram of a noisy signal, |X_k|^2 suitably scaled, has a standard
+//! deviation as large as its mean however long the signal is. Welch's method
+//! cuts the signal into overlapping windowed segments and averages their
+//! periodograms, trading frequency resolution for variance. the conventions
+//! (periodic windows, one-sided doubling, scaling) are SciPy's, so the
+//! results match `scipy.signal.welch` and `scipy.signal.periodogram`.
+
+use num::complex::Complex;
+
+use crate::float::{zero, FftFloat};
+use crate::plan::Direction;
+use crate::spectrum::folded;
+use crate::window::{Symmetry, Window};
+
+/// what is removed from each segment before its fft
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Detrend {
+    /// nothing
+    None,
+    /// the segment's mean, so DC offsets don't leak into the low bins
+    #[default]
+    Constant,
+    /// the segment's least-squares straight line
+    Linear,
+}
+
+/// the units of a power spectrum estimate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PsdScaling {
+    /// power spectral density in V^2/Hz; integrating over frequency gives the
+    /// signal's power, so noise reads the same whatever the segment length
+    #[default]
+    Density,
+    /// power spectrum in V^2; a sinusoid of amplitude A on a bin reads A^2 / 2
+    Spectrum,
+}
+
+/// estimate the one-sided power spectral density of a real signal sampled
+/// at `fs` with Welch's method, like `scipy.signal.welch`; returns the
+/// frequencies and the estimate at each
+///
+/// the signal is cut into segments of `segment_len` samples starting every
+/// `segment_len - overlap` samples (samples after the last whole segment are
+/// dropped), each is detrended and multiplied by the periodic `window`, and
+/// their periodograms are averaged. a `segment_len` longer than the signal is
+/// cut down to the signal's length. panics if `signal` is empty, `segment_len`
+/// is zero or `overlap` isn't less than it
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{Detrend, PsdScaling, Window};
+///
+/// // a 2 V amplitude, 1 kHz sinusoid sampled at 8 kHz reads 2 V^2 with
+/// // spectrum scaling
+/// let signal: Vec<f64> = (0..8000).map(
+///     |n| 2.0 * (2.0 * std::f64::consts::PI * 1000.0 * n as f64 / 8000.0).sin()
+/// ).collect();
+/// let (frequencies, power) = fft_rust::welch_psd(
+///     &signal, 8000., 256, 128, Window::Hann, Detrend::Constant, PsdScaling::Spectrum
+/// );
+/// assert_eq!(frequencies[32], 1000.);
+/// assert!((power[32] - 2.0).abs() < 1e-9);
+/// ```
+pub fn welch_psd<T: FftFloat>(
+    signal: &[T], fs: T, segment_len: usize, overlap: usize, window: Window,
+    detrend: Detrend, scaling: PsdScaling
+) -> (Vec<T>, Vec<T>) {
+    let segments = Segments::new(signal.len(), segment_len, overlap, window);
+    let spectra = segments.spectra(signal, detrend);
+    let scale = segments.scale(fs, scaling);
+    let values = segments.average(|k| {
+        spectra.iter().skip(k).step_by(segments.bins).map(|x| x.norm_sqr()).sum::<T>() * scale
+    });
+    (segments.frequencies(fs), values)
+}
+
+/// the periodogram of a real signal sampled at `fs`, like
+/// `scipy.signal.periodogram`: Welch's method with one segment covering the
+/// whole signal; panics if `signal` is empty
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{Detrend, PsdScaling, Window};
+///
+/// let (frequencies, density) = fft_rust::periodogram(
+///     &[1f64, 2., 3., 4.], 1., Window::Rectangular, Detrend::None, PsdScaling::Density
+/// );
+/// assert_eq!(frequencies, vec![0., 0.25, 0.5]);
+/// assert_eq!(density, vec![25., 4., 1.]);
+/// ```
+pub fn periodogram<T: FftFloat>(
+    signal: &[T], fs: T, window: Window, detrend: Detrend, scaling: PsdScaling
+) -> (Vec<T>, Vec<T>) {
+    welch_psd(signal, fs, signal.len(), 0, window, detrend, scaling)
+}
+
+/// how a signal is cut up for Welch's method; shared with the cross-spectral
+/// estimates, which cut two signals the same way
+pub(crate) struct Segments<T: FftFloat> {
+    pub(crate) len: usize,
+    pub(crate) step: usize,
+    pub(crate) count: usize,
+    pub(crate) bins: usize,
+    pub(crate) window: Vec<T>,
+}
+
+impl<T: FftFloat> Segments<T> {
+    pub(crate) fn new(
+        signal_len: usize, segment_len: usize, overlap: usize, window: Window
+    ) -> Segments<T> {
+        assert!(signal_len > 0, "a power spectrum needs at least one sample");
+        assert!(segment_len > 0, "segments need at least one sample");
+        assert!(
+            overlap < segment_len,
+            "overlap {} isn't less than the segment length {}", overlap, segment_len
+        );
+        let len = segment_len.min(signal_len);
+        let step = segment_len - overlap;
+        Segments {
+            len,
+            step,
+            count: (signal_len - len) / step + 1,
+            bins: len / 2 + 1,
+            window: crate::window(window, len, Symmetry::Periodic),
+        }
+    }
+
+    /// the one-sided spectrum of every detrended, windowed segment, one row
+    /// of `bins` per segment
+    pub(crate) fn spectra(&self, signal: &[T], detrend: Detrend) -> Vec<Complex<T>> {
+        let mut spectra = vec![zero(); self.count * self.len];
+        for (m, row) in spectra.chunks_exact_mut(self.len).enumerate() {
+            let segment = &signal[m * self.step..m * self.step + self.len];
+            let trend = trend(segment, detrend);
+            for (n, x) in row.iter_mut().enumerate() {
+                *x = Complex::new((segment[n] - trend(n)) * self.window[n], T::zero());
+            }
+        }
+        crate::batch(&mut spectra, self.len, self.count, Direction::Forward);
+        spectra.chunks_exact(self.len).flat_map(|row| row[..self.bins].iter().copied()).collect()
+    }
+
+    /// what a sum of squared bins is multiplied by to give the estimate
+    pub(crate) fn scale(&self, fs: T, scaling: PsdScaling) -> T {
+        match scaling {
+            PsdScaling::Density => T::one() / (fs * self.window.iter().map(|w| *w * *w).sum()),
+            PsdScaling::Spectrum => {
+                let sum: T = self.window.iter().copied().sum();
+                T::one() / (sum * sum)
+            }
+        }
+    }
+
+    /// `sum(k)` averaged over the segments for every bin, doubled for the
+    /// bins whose negative frequency twin was folded in
+    pub(crate) fn average<U, F>(&self, sum: F) -> Vec<U>
+    where
+        U: std::ops::Mul<T, Output = U> + std::ops::Div<T, Output = U>,
+        F: Fn(usize) -> U,
+    {
+        let count = T::from_usize(self.count);
+        let two = T::from_f64(2.0);
+        (0..self.bins).map(|k| {
+            let mean = sum(k) / count;
+            if folded(k, self.len) { mean * two } else { mean }
+        }).collect()
+    }
+
+    /// the frequency of each bin
+    pub(crate) fn frequencies(&self, fs: T) -> Vec<T> {
+        crate::rfftfreq(self.len, T::one() / fs)
+    }
+}
+
+/// the trend `detrend` takes out of `segment`, as a function of the index
+fn trend<T: FftFloat>(segment: &[T], detrend: Detrend) -> impl Fn(usize) -> T {
+    let n = T::from_usize(segment.len());
+    let mean = segment.iter().copied().sum::<T>() / n;
+    let (offset, slope) = match detrend {
+        Detrend::None => (T::zero(), T::zero()),
+        Detrend::Constant => (mean, T::zero()),
+        Detrend::Linear => {
+            // least squares about the middle index, where the slope and the
+            // offset are uncorrelated
+            let middle = (n - T::one()) / T::from_f64(2.0);
+            let (mut covariance, mut variance) = (T::zero(), T::zero());
+            for (i, x) in segment.iter().enumerate() {
+                let d = T::from_usize(i) - middle;
+                covariance += d * (*x - mean);
+                variance += d * d;
+            }
+            let slope = if variance > T::zero() { covariance / variance } else { T::zero() };
+            (mean - slope * middle, slope)
+        }
+    };
+    move |i| offset + slope * T::from_usize(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::noise;
+
+    #[test]
+    fn periodogram_test() {
+        // by hand: rfft [10, -2 + 2i, -2], squared over fs * N, middle doubled
+        let signal = [1f64, 2., 3., 4.];
+        let density = |signal: &[f64], detrend| {
+            periodogram(signal, 1.0, Window::Rectangular, detrend, PsdScaling::Density).1
+        };
+        assert_eq!(density(&signal, Detrend::None), vec![25., 4., 1.]);
+        assert_eq!(density(&signal, Detrend::Constant), vec![0., 4., 1.]);
+        assert!(density(&signal, Detrend::Linear).iter().all(|x| x.abs() < 1e-20));
+        let (frequencies, power) = periodogram(
+            &signal, 2.0, Window::Rectangular, Detrend::None, PsdScaling::Spectrum
+        );
+        assert_eq!(frequencies, vec![0., 0.5, 1.]);
+        assert_eq!(power, vec![6.25, 1., 0.25]);
+
+        // odd lengths have no Nyquist bin, so the last bin is doubled too
+        let odd = density(&[1., 0., 0.], Detrend::None);
+        assert!(odd.iter().zip([1. / 3., 2. / 3.]).all(|(a, b)| (a - b).abs() < 1e-15));
+    }
+
+    #[test]
+    fn welch_averages_segments_test() {
+        // 3 segments of 4 starting every 2 samples; the last sample is dropped
+        let signal: Vec<f64> = noise(9, 1);
+        let (_, welch) = welch_psd(
+            &signal, 1.0, 4, 2, Window::Hann, Detrend::Linear, PsdScaling::Density
+        );
+        let mut expected = [0.0; 3];
+        for start in [0, 2, 4] {
+            let (_, density) = periodogram(
+                &signal[start..start + 4], 1.0, Window::Hann, Detrend::Linear, PsdScaling::Density
+            );
+            expected.iter_mut().zip(density).for_each(|(e, d)| *e += d / 3.0);
+        }
+        for (a, b) in welch.iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn welch_noise_density_test() {
+        // white noise of variance s^2 has a one-sided density of 2 s^2 / fs,
+        // and the density integrates to the variance
+        let fs = 1000.0;
+        let signal: Vec<f64> = noise(100_000, 7).iter().map(|x| 0.5 * x).collect();
+        let (frequencies, density) = welch_psd(
+            &signal, fs, 1024, 512, Window::Hann, Detrend::Constant, PsdScaling::Density
+        );
+        let interior = &density[10..density.len() - 10];
+        let mean = interior.iter().sum::<f64>() / interior.len() as f64;
+        assert!((mean / (2.0 * 0.25 / fs) - 1.0).abs() < 0.02);
+        let power: f64 = density.iter().sum::<f64>() * frequencies[1];
+        assert!((power / 0.25 - 1.0).abs() < 0.02);
+        // averaging ~200 segments leaves a small spread around the mean
+        let spread = interior.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / interior.len() as f64;
+        assert!(spread.sqrt() / mean < 0.15);
+    }
+
+    #[test]
+    fn welch_f32_test() {
+        let signal: Vec<f32> = (0..4096).map(
+            |n| (2.0 * std::f32::consts::PI * n as f32 / 16.0).sin()
+        ).collect();
+        let (frequencies, power) = welch_psd(
+            &signal, 16.0, 256, 128, Window::FlatTop, Detrend::None, PsdScaling::Spectrum
+        );
+        assert_eq!(frequencies[16], 1.0);
+        assert!((power[16] - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn long_segment_test() {
+        // segments longer than the signal shrink to fit, like SciPy
+        let signal = noise(100, 3);
+        assert_eq!(
+            welch_psd(&signal, 1.0, 256, 128, Window::Hann, Detrend::Constant, PsdScaling::Density),
+            periodogram(&signal, 1.0, Window::Hann, Detrend::Constant, PsdScaling::Density)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn welch_rejects_full_overlap_test() {
+        welch_psd(&[1f64; 16], 1.0, 8, 8, Window::Hann, Detrend::None, PsdScaling::Density);
+    }
+}
@@ -0,0 +1,184 @@
+//! Rader's algorithm for prime lengths
+//!
+//! for a prime p the nonzero indices 1..p form a cyclic group under
+//! multiplication mod p, generated by a primitive root g. writing n = g^q and
+//! k = g^-r turns the DFT (minus the zero terms) into a cyclic convolution of
+//! length p - 1, which is done with ffts of length p - 1.
+
+use num::complex::Complex;
+
+use crate::float::{twiddle, zero, FftFloat};
+use crate::plan::{Algorithm, Direction, FftPlan};
+
+#[derive(Debug, Clone)]
+pub(crate) struct Rader<T: FftFloat> {
+    len: usize,
+    inverse: bool,
+    // g^q mod p for q in 0..p-1: where each convolution input comes from
+    input_index: Vec<usize>,
+    // g^-q mod p for q in 0..p-1: where each convolution output goes
+    output_index: Vec<usize>,
+    // fft of the permuted twiddles w^(g^-q)
+    kernel: Vec<Complex<T>>,
+    forward: FftPlan<T>,
+    backward: FftPlan<T>,
+    buffer: Vec<Complex<T>>,
+}
+
+impl<T: FftFloat> Rader<T> {
+    /// panics if `len` is not an odd prime
+    pub(crate) fn new(len: usize, direction: Direction, algorithm: Algorithm) -> Rader<T> {
+        assert!(len > 2 && is_prime(len), "Rader's algorithm needs an odd prime length");
+        let inverse = direction == Direction::Inverse;
+        let sign = if inverse { 1f64 } else { -1f64 };
+
+        let generator = primitive_root(len);
+        let generator_inverse = mod_pow(generator, len - 2, len);
+        let input_index: Vec<usize> = (0..len - 1).scan(1, |g_q, _| {
+            let current = *g_q;
+            *g_q = *g_q * generator % len;
+            Some(current)
+        }).collect();
+        let output_index: Vec<usize> = (0..len - 1).scan(1, |g_q, _| {
+            let current = *g_q;
+            *g_q = *g_q * generator_inverse % len;
+            Some(current)
+        }).collect();
+
+        let mut forward = FftPlan::with_algorithm(len - 1, Direction::Forward, algorithm);
+        let backward = FftPlan::with_algorithm(len - 1, Direction::Inverse, algorithm);
+
+        let mut kernel: Vec<_> = output_index.iter().map(|k| twiddle(*k, len, sign)).collect();
+        forward.process(&mut kernel);
+
+        Rader {
+            len,
+            inverse,
+            input_index,
+            output_index,
+            kernel,
+            forward,
+            backward,
+            buffer: vec![zero(); len - 1],
+        }
+    }
+
+    pub(crate) fn process(&mut self, data: &mut [Complex<T>]) {
+        let x_0 = data[0];
+        let total: Complex<T> = data.iter().sum();
+
+        for (b, i) in self.buffer.iter_mut().zip(self.input_index.iter()) {
+            *b = data[*i];
+        }
+        // the inverse plan's 1/(p-1) is the scaling the convolution needs
+        self.forward.process(&mut self.buffer);
+        for (b, k) in self.buffer.iter_mut().zip(self.kernel.iter()) {
+            *b *= k;
+        }
+        self.backward.process(&mut self.buffer);
+
+        let scale = if self.inverse { T::from_usize(self.len) } else { T::one() };
+        data[0] = total / scale;
+        for (b, k) in self.buffer.iter().zip(self.output_index.iter()) {
+            data[*k] = (x_0 + b) / scale;
+        }
+    }
+}
+
+/// true if `num` is prime
+pub(crate) fn is_prime(num: usize) -> bool {
+    if num < 2 {
+        return false;
+    }
+    let mut i = 2;
+    while i * i <= num {
+        if num.is_multiple_of(i) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// the distinct prime factors of `num`, smallest first
+pub(crate) fn prime_factors(mut num: usize) -> Vec<usize> {
+    let mut factors = vec![];
+    let mut i = 2;
+    while i * i <= num {
+        if num.is_multiple_of(i) {
+            factors.push(i);
+            while num.is_multiple_of(i) {
+                num /= i;
+            }
+        }
+        i += 1;
+    }
+    if num > 1 {
+        factors.push(num);
+    }
+    factors
+}
+
+/// the smallest generator of the multiplicative group mod the prime `p`
+fn primitive_root(p: usize) -> usize {
+    let factors = prime_factors(p - 1);
+    (2..p).find(
+        |g| factors.iter().all(|f| mod_pow(*g, (p - 1) / f, p) != 1)
+    ).unwrap_or(1)
+}
+
+/// base^exp mod modulus
+fn mod_pow(base: usize, mut exp: usize, modulus: usize) -> usize {
+    let modulus = modulus as u128;
+    let mut base = base as u128 % modulus;
+    let mut result = 1u128;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    result as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{dft, signal};
+
+    #[test]
+    fn rader_matches_dft_test() {
+        for len in [3, 5, 7, 11, 13, 17, 23, 97] {
+            let input = signal(len, 1);
+            for (direction, inverse) in [(Direction::Forward, false), (Direction::Inverse, true)] {
+                let mut output = input.clone();
+                Rader::<f64>::new(len, direction, Algorithm::Recursive).process(&mut output);
+                for (a, b) in output.iter().zip(dft(&input, inverse).iter()) {
+                    assert!((a - b).norm() < 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn is_prime_test() {
+        let primes: Vec<_> = (0..30).filter(|n| is_prime(*n)).collect();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+    }
+
+    #[test]
+    fn prime_factors_test() {
+        assert_eq!(prime_factors(1), vec![]);
+        assert_eq!(prime_factors(12), vec![2, 3]);
+        assert_eq!(prime_factors(1000), vec![2, 5]);
+        assert_eq!(prime_factors(97), vec![97]);
+    }
+
+    #[test]
+    fn primitive_root_test() {
+        assert_eq!(primitive_root(7), 3);
+        assert_eq!(primitive_root(11), 2);
+        assert_eq!(primitive_root(23), 5);
+    }
+}
@@ -0,0 +1,142 @@
+//! ffts of real-valued signals
+//!
+//! the spectrum of a real signal is Hermitian (X_(N-k) = conj(X_k)), so only
+//! the first N/2 + 1 bins carry information. for even N the signal is packed
+//! into a half-length complex signal z_k = x_2k + i * x_(2k+1), transformed
+//! once, and the even/odd spectra are untangled afterwards, which is about
+//! twice as fast as running a full complex fft.
+
+use num::complex::Complex;
+
+use crate::float::{twiddle, FftFloat};
+use crate::plan::{Direction, FftPlan};
+
+/// compute the non-redundant half of the spectrum of a real signal;
+/// returns the N/2 + 1 bins from DC up to (and including) Nyquist
+///
+/// panics if `input` is empty
+///
+/// # Example
+///
+/// ```
+/// use num::complex::Complex;
+///
+/// let answer = &mut fft_rust::rfft(&[4., 3., -5., 1.]);
+/// assert_eq!(fft_rust::approximate_complex(answer), &mut vec![
+///     Complex::new(3f64, 0f64), Complex::new(9f64, -2f64), Complex::new(-5f64, 0f64)
+/// ]);
+/// ```
+pub fn rfft<T: FftFloat>(input: &[T]) -> Vec<Complex<T>> {
+    let n = input.len();
+    assert!(n > 0, "rfft needs at least one sample");
+    if !n.is_multiple_of(2) {
+        let mut full = crate::float_array_to_complex(input.to_vec());
+        FftPlan::new(n, Direction::Forward).process(&mut full);
+        full.truncate(n / 2 + 1);
+        return full;
+    }
+
+    let half = n / 2;
+    let two = T::from_f64(2.0);
+    let mut z: Vec<_> = input.chunks_exact(2).map(|x| Complex::new(x[0], x[1])).collect();
+    FftPlan::new(half, Direction::Forward).process(&mut z);
+
+    (0..=half).map(|k| {
+        let z_k = z[k % half];
+        let z_conj = z[(half - k) % half].conj();
+        let even = (z_k + z_conj) / two;
+        let odd = (z_k - z_conj) / Complex::new(T::zero(), two);
+        even + twiddle::<T>(k, n, -1f64) * odd
+    }).collect()
+}
+
+/// invert `rfft`: given the N/2 + 1 bins of a real signal's spectrum and the
+/// signal length N, return the real signal (scaled by 1/N like `ifft`)
+///
+/// the length is needed because N and N + 1 give the same number of bins
+/// when N is even; panics if `input.len()` is not `len / 2 + 1`
+///
+/// # Example
+///
+/// ```
+/// let spectrum = fft_rust::rfft(&[2f64, 7., -1.]);
+/// let answer: Vec<f64> = fft_rust::irfft(&spectrum, 3).iter().map(|x| x.round()).collect();
+/// assert_eq!(answer, vec![2., 7., -1.]);
+/// ```
+pub fn irfft<T: FftFloat>(input: &[Complex<T>], len: usize) -> Vec<T> {
+    assert!(len > 0, "irfft needs at least one output sample");
+    assert_eq!(
+        input.len(), len / 2 + 1,
+        "a length {} signal has {} bins, got {}", len, len / 2 + 1, input.len()
+    );
+    if !len.is_multiple_of(2) {
+        let mut full: Vec<_> = (0..len).map(
+            |k| if k < input.len() { input[k] } else { input[len - k].conj() }
+        ).collect();
+        FftPlan::new(len, Direction::Inverse).process(&mut full);
+        return full.iter().map(|x| x.re).collect();
+    }
+
+    let half = len / 2;
+    let two = T::from_f64(2.0);
+    let mut z: Vec<_> = (0..half).map(|k| {
+        let x_k = input[k];
+        let x_conj = input[half - k].conj();
+        let even = (x_k + x_conj) / two;
+        let odd = (x_k - x_conj) / two * twiddle(k, len, 1f64);
+        even + Complex::new(T::zero(), T::one()) * odd
+    }).collect();
+    FftPlan::new(half, Direction::Inverse).process(&mut z);
+
+    z.iter().flat_map(|z_k| [z_k.re, z_k.im]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft_exact;
+
+    #[test]
+    fn rfft_matches_fft_test() {
+        for len in [1, 2, 3, 4, 8, 10, 15, 16, 1000] {
+            let input = crate::tests::noise(len, 1);
+            let expected = fft_exact(&crate::float_array_to_complex(input.clone()));
+            let output = rfft(&input);
+            assert_eq!(output.len(), len / 2 + 1);
+            for (a, b) in output.iter().zip(expected.iter()) {
+                assert!((a - b).norm() < 1e-9, "length {} differs", len);
+            }
+        }
+    }
+
+    #[test]
+    fn irfft_reverses_rfft_test() {
+        for len in [1, 2, 3, 4, 8, 10, 15, 16, 1000] {
+            let input = crate::tests::noise(len, 1);
+            let output = irfft(&rfft(&input), len);
+            assert_eq!(output.len(), len);
+            for (a, b) in output.iter().zip(input.iter()) {
+                assert!((a - b).abs() < 1e-9, "length {} differs", len);
+            }
+        }
+    }
+
+    #[test]
+    fn rfft_f32_test() {
+        let input: Vec<f32> = crate::tests::noise(64, 1).iter().map(|x| *x as f32).collect();
+        let spectrum = rfft(&input);
+        let expected = rfft(&input.iter().map(|x| *x as f64).collect::<Vec<_>>());
+        for (a, b) in spectrum.iter().zip(expected.iter()) {
+            assert!((a.re as f64 - b.re).abs() < 1e-4 && (a.im as f64 - b.im).abs() < 1e-4);
+        }
+        for (a, b) in irfft(&spectrum, 64).iter().zip(input.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn irfft_rejects_wrong_bin_count_test() {
+        irfft(&[Complex::new(1f64, 0f64); 4], 4);
+    }
+}
@@ -0,0 +1,323 @@
+//! vectorized butterfly passes
+//!
+//! the radix-2 combine step (used by every power-of-two transform) and the
+//! radix-4 step of the mixed-radix kernel do the same few complex
+//! multiply-adds for every k, so they're written once over `ComplexVector`
+//! and instantiated per instruction set:
+//! - x86_64: AVX2+FMA, AVX or SSE2, picked at runtime with `is_x86_feature_detected!`
+//! - aarch64: NEON, which every aarch64 cpu has
+//! - anything else: the scalar loops
+//!
+//! the SSE2, AVX and NEON kernels do the same arithmetic as the scalar loops
+//! in the same order, so their output is bit-identical. the FMA kernel rounds
+//! each complex product once instead of twice, so it can differ from the
+//! others in the last bit (it is slightly more accurate, not less).
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+use std::any::TypeId;
+use num::complex::Complex;
+
+use crate::float::FftFloat;
+
+#[cfg(target_arch = "aarch64")]
+mod aarch64;
+#[cfg(target_arch = "x86_64")]
+mod x86_64;
+
+#[cfg(target_arch = "aarch64")]
+use self::aarch64 as arch;
+#[cfg(target_arch = "x86_64")]
+use self::x86_64 as arch;
+
+/// the radix-2 butterfly over the halves of a transform:
+/// y_e[k], y_o[k] = y_e[k] +- twiddles[k * twiddle_stride] * y_o[k],
+/// halved when `inverse` is set
+pub(crate) fn radix2<T: FftFloat>(
+    y_e: &mut [Complex<T>], y_o: &mut [Complex<T>],
+    twiddles: &[Complex<T>], twiddle_stride: usize, inverse: bool
+) {
+    assert_eq!(y_e.len(), y_o.len(), "butterfly halves must be the same length");
+    // the vector kernels gather twiddles without bounds checks
+    assert!(
+        y_e.is_empty() || (y_e.len() - 1) * twiddle_stride < twiddles.len(),
+        "{} twiddles are too few for {} butterflies at stride {}", twiddles.len(), y_e.len(), twiddle_stride
+    );
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    {
+        // SAFETY: each branch only casts to the type T was just checked to
+        // be, and the twiddle table covers every index the kernels read
+        if is::<T, f64>() {
+            return unsafe { arch::radix2_f64(
+                retype_mut(y_e), retype_mut(y_o), retype(twiddles), twiddle_stride, inverse
+            ) };
+        }
+        if is::<T, f32>() {
+            return unsafe { arch::radix2_f32(
+                retype_mut(y_e), retype_mut(y_o), retype(twiddles), twiddle_stride, inverse
+            ) };
+        }
+    }
+    radix2_scalar(y_e, y_o, twiddles, twiddle_stride, inverse);
+}
+
+/// the radix-4 step of a mixed-radix transform: `data` holds four
+/// length-m sub-transforms back to back, and column k1 (data[k1 + j * m]
+/// for j in 0..4) is twiddled by twiddles[j * k1 * twiddle_stride] and
+/// replaced by its 4-point DFT
+pub(crate) fn radix4<T: FftFloat>(
+    data: &mut [Complex<T>], twiddles: &[Complex<T>], twiddle_stride: usize, inverse: bool
+) {
+    assert!(data.len().is_multiple_of(4), "radix-4 data must split into four rows");
+    // the vector kernels gather twiddles without bounds checks
+    let m = data.len() / 4;
+    assert!(
+        m == 0 || 3 * (m - 1) * twiddle_stride < twiddles.len(),
+        "{} twiddles are too few for {} radix-4 columns at stride {}", twiddles.len(), m, twiddle_stride
+    );
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    {
+        // SAFETY: each branch only casts to the type T was just checked to
+        // be, and the twiddle table covers every index the kernels read
+        if is::<T, f64>() {
+            return unsafe {
+                arch::radix4_f64(retype_mut(data), retype(twiddles), twiddle_stride, inverse)
+            };
+        }
+        if is::<T, f32>() {
+            return unsafe {
+                arch::radix4_f32(retype_mut(data), retype(twiddles), twiddle_stride, inverse)
+            };
+        }
+    }
+    radix4_scalar(data, twiddles, twiddle_stride, inverse);
+}
+
+fn radix2_scalar<T: FftFloat>(
+    y_e: &mut [Complex<T>], y_o: &mut [Complex<T>],
+    twiddles: &[Complex<T>], twiddle_stride: usize, inverse: bool
+) {
+    let two = T::from_f64(2.0);
+    for k in 0..y_e.len() {
+        let temp = twiddles[k * twiddle_stride] * y_o[k];
+        let even = y_e[k];
+        if inverse {
+            y_e[k] = (even + temp) / two;
+            y_o[k] = (even - temp) / two;
+        } else {
+            y_e[k] = even + temp;
+            y_o[k] = even - temp;
+        }
+    }
+}
+
+fn radix4_scalar<T: FftFloat>(
+    data: &mut [Complex<T>], twiddles: &[Complex<T>], twiddle_stride: usize, inverse: bool
+) {
+    radix4_columns(data, 0, twiddles, twiddle_stride, inverse);
+}
+
+/// the scalar radix-4 step for columns `start..m`; the vector kernels use it
+/// for the columns left over after the last full vector
+fn radix4_columns<T: FftFloat>(
+    data: &mut [Complex<T>], start: usize,
+    twiddles: &[Complex<T>], twiddle_stride: usize, inverse: bool
+) {
+    let m = data.len() / 4;
+    let sign = T::from_f64(if inverse { 1f64 } else { -1f64 });
+    for k1 in start..m {
+        let a = data[k1] * twiddles[0];
+        let b = data[m + k1] * twiddles[k1 * twiddle_stride];
+        let c = data[2 * m + k1] * twiddles[2 * k1 * twiddle_stride];
+        let d = data[3 * m + k1] * twiddles[3 * k1 * twiddle_stride];
+        let b_minus_d = b - d;
+        let diff = Complex::new(-b_minus_d.im, b_minus_d.re) * sign;
+        data[k1] = a + b + c + d;
+        data[m + k1] = a - c + diff;
+        data[2 * m + k1] = a + c - b - d;
+        data[3 * m + k1] = a - c - diff;
+    }
+}
+
+/// `WIDTH` complex numbers packed into one register
+///
+/// every method is `unsafe` because it has to be called from a function
+/// compiled with the matching target features
+trait ComplexVector: Copy {
+    type Float: FftFloat;
+    const WIDTH: usize;
+
+    /// `WIDTH` consecutive values starting at `ptr`
+    unsafe fn load(ptr: *const Complex<Self::Float>) -> Self;
+    /// `WIDTH` values `stride` apart starting at `ptr`
+    unsafe fn gather(ptr: *const Complex<Self::Float>, stride: usize) -> Self;
+    unsafe fn store(self, ptr: *mut Complex<Self::Float>);
+    unsafe fn add(self, other: Self) -> Self;
+    unsafe fn sub(self, other: Self) -> Self;
+    /// lane-wise complex product, rounded the same way as `Complex`'s `*`
+    unsafe fn mul(self, other: Self) -> Self;
+    unsafe fn half(self) -> Self;
+    /// multiply by -i, or by i when `inverse` is set
+    unsafe fn rotate(self, inverse: bool) -> Self;
+}
+
+/// `radix2` on vectors of `V::WIDTH`, finishing any leftover values with the
+/// scalar loop
+#[inline(always)]
+unsafe fn radix2_pass<V: ComplexVector>(
+    y_e: &mut [Complex<V::Float>], y_o: &mut [Complex<V::Float>],
+    twiddles: &[Complex<V::Float>], twiddle_stride: usize, inverse: bool
+) {
+    let vectors = y_e.len() / V::WIDTH * V::WIDTH;
+    for k in (0..vectors).step_by(V::WIDTH) {
+        let temp = V::gather(twiddles.as_ptr().add(k * twiddle_stride), twiddle_stride)
+            .mul(V::load(y_o.as_ptr().add(k)));
+        let even = V::load(y_e.as_ptr().add(k));
+        let (sum, diff) = (even.add(temp), even.sub(temp));
+        if inverse {
+            sum.half().store(y_e.as_mut_ptr().add(k));
+            diff.half().store(y_o.as_mut_ptr().add(k));
+        } else {
+            sum.store(y_e.as_mut_ptr().add(k));
+            diff.store(y_o.as_mut_ptr().add(k));
+        }
+    }
+    radix2_scalar(
+        &mut y_e[vectors..], &mut y_o[vectors..],
+        twiddles.get(vectors * twiddle_stride..).unwrap_or(&[]), twiddle_stride, inverse
+    );
+}
+
+/// `radix4` on `V::WIDTH` columns at a time
+#[inline(always)]
+unsafe fn radix4_pass<V: ComplexVector>(
+    data: &mut [Complex<V::Float>], twiddles: &[Complex<V::Float>],
+    twiddle_stride: usize, inverse: bool
+) {
+    let m = data.len() / 4;
+    let vectors = m / V::WIDTH * V::WIDTH;
+    let rows = data.as_mut_ptr();
+    let w = twiddles.as_ptr();
+    for k1 in (0..vectors).step_by(V::WIDTH) {
+        let a = V::load(rows.add(k1)).mul(V::gather(w, 0));
+        let b = V::load(rows.add(m + k1))
+            .mul(V::gather(w.add(k1 * twiddle_stride), twiddle_stride));
+        let c = V::load(rows.add(2 * m + k1))
+            .mul(V::gather(w.add(2 * k1 * twiddle_stride), 2 * twiddle_stride));
+        let d = V::load(rows.add(3 * m + k1))
+            .mul(V::gather(w.add(3 * k1 * twiddle_stride), 3 * twiddle_stride));
+        let diff = b.sub(d).rotate(inverse);
+        a.add(b).add(c).add(d).store(rows.add(k1));
+        a.sub(c).add(diff).store(rows.add(m + k1));
+        a.add(c).sub(b).sub(d).store(rows.add(2 * m + k1));
+        a.sub(c).sub(diff).store(rows.add(3 * m + k1));
+    }
+    radix4_columns(data, vectors, twiddles, twiddle_stride, inverse);
+}
+
+/// true if `T` and `U` are the same type
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn is<T: 'static, U: 'static>() -> bool {
+    TypeId::of::<T>() == TypeId::of::<U>()
+}
+
+/// view a slice of `Complex<T>` as a slice of `Complex<U>`
+///
+/// # Safety
+///
+/// `T` and `U` must be the same type (see `is`)
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+unsafe fn retype<T, U>(x: &[Complex<T>]) -> &[Complex<U>] {
+    std::slice::from_raw_parts(x.as_ptr() as *const Complex<U>, x.len())
+}
+
+/// `retype` for mutable slices
+///
+/// # Safety
+///
+/// `T` and `U` must be the same type (see `is`)
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+unsafe fn retype_mut<T, U>(x: &mut [Complex<T>]) -> &mut [Complex<U>] {
+    std::slice::from_raw_parts_mut(x.as_mut_ptr() as *mut Complex<U>, x.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn signal<T: FftFloat>(len: usize, seed: u64) -> Vec<Complex<T>> {
+        crate::tests::signal(len, seed).iter().map(
+            |x| Complex::new(T::from_f64(x.re), T::from_f64(x.im))
+        ).collect()
+    }
+
+    fn close<T: FftFloat>(a: &[Complex<T>], b: &[Complex<T>], tolerance: f64) -> bool {
+        a.iter().zip(b.iter()).all(|(x, y)| (x - y).norm() <= T::from_f64(tolerance))
+    }
+
+    fn radix2_matches_scalar<T: FftFloat>(tolerance: f64) {
+        for (len, stride) in [(1, 1), (3, 1), (4, 2), (8, 2), (13, 3), (64, 1)] {
+            // a full table, and the shortest one the butterflies allow
+            for (twiddles, inverse) in [
+                (signal::<T>(len * stride, 1), false), (signal::<T>(len * stride, 1), true),
+                (signal::<T>((len - 1) * stride + 1, 1), false),
+            ] {
+                let (mut y_e, mut y_o) = (signal::<T>(len, 2), signal::<T>(len, 3));
+                let (mut s_e, mut s_o) = (y_e.clone(), y_o.clone());
+                radix2(&mut y_e, &mut y_o, &twiddles, stride, inverse);
+                radix2_scalar(&mut s_e, &mut s_o, &twiddles, stride, inverse);
+                assert!(close(&y_e, &s_e, tolerance) && close(&y_o, &s_o, tolerance));
+            }
+        }
+    }
+
+    fn radix4_matches_scalar<T: FftFloat>(tolerance: f64) {
+        for (m, stride) in [(1, 1), (3, 2), (4, 2), (8, 1), (13, 3)] {
+            for (twiddles, inverse) in [
+                (signal::<T>(4 * m * stride, 1), false), (signal::<T>(4 * m * stride, 1), true),
+                (signal::<T>(3 * (m - 1) * stride + 1, 1), false),
+            ] {
+                let mut data = signal::<T>(4 * m, 2);
+                let mut expected = data.clone();
+                radix4(&mut data, &twiddles, stride, inverse);
+                radix4_scalar(&mut expected, &twiddles, stride, inverse);
+                assert!(close(&data, &expected, tolerance));
+            }
+        }
+    }
+
+    #[test]
+    fn radix2_test() {
+        radix2_matches_scalar::<f64>(1e-12);
+        radix2_matches_scalar::<f32>(1e-4);
+    }
+
+    #[test]
+    fn radix4_test() {
+        radix4_matches_scalar::<f64>(1e-12);
+        radix4_matches_scalar::<f32>(1e-4);
+    }
+
+    #[test]
+    fn radix4_matches_dft_test() {
+        // with unit twiddles each column is a plain 4-point DFT
+        let twiddles = vec![Complex::new(1f64, 0f64); 4];
+        let mut data: Vec<_> = (0..4).map(|x| Complex::new(x as f64, 1.0 - x as f64)).collect();
+        let expected = crate::tests::dft(&data, false);
+        radix4(&mut data, &twiddles, 0, false);
+        assert!(close(&data, &expected, 1e-12));
+    }
+
+    #[test]
+    #[should_panic]
+    fn radix2_rejects_short_twiddles_test() {
+        let (mut y_e, mut y_o) = (signal::<f64>(16, 2), signal::<f64>(16, 3));
+        radix2(&mut y_e, &mut y_o, &signal::<f64>(30, 1), 2, false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn radix4_rejects_short_twiddles_test() {
+        let mut data = signal::<f64>(32, 2);
+        radix4(&mut data, &signal::<f64>(21, 1), 1, false);
+    }
+}
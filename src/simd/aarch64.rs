@@ -0,0 +1,161 @@
+//! NEON kernels
+//!
+//! NEON is part of the aarch64 baseline, so there's nothing to detect
+
+use std::arch::aarch64::*;
+use num::complex::Complex;
+
+use super::{radix2_pass, radix4_pass, ComplexVector};
+
+pub(super) unsafe fn radix2_f64(
+    y_e: &mut [Complex<f64>], y_o: &mut [Complex<f64>],
+    twiddles: &[Complex<f64>], twiddle_stride: usize, inverse: bool
+) {
+    radix2_pass::<NeonF64>(y_e, y_o, twiddles, twiddle_stride, inverse)
+}
+
+pub(super) unsafe fn radix2_f32(
+    y_e: &mut [Complex<f32>], y_o: &mut [Complex<f32>],
+    twiddles: &[Complex<f32>], twiddle_stride: usize, inverse: bool
+) {
+    radix2_pass::<NeonF32>(y_e, y_o, twiddles, twiddle_stride, inverse)
+}
+
+pub(super) unsafe fn radix4_f64(
+    data: &mut [Complex<f64>], twiddles: &[Complex<f64>], twiddle_stride: usize, inverse: bool
+) {
+    radix4_pass::<NeonF64>(data, twiddles, twiddle_stride, inverse)
+}
+
+pub(super) unsafe fn radix4_f32(
+    data: &mut [Complex<f32>], twiddles: &[Complex<f32>], twiddle_stride: usize, inverse: bool
+) {
+    radix4_pass::<NeonF32>(data, twiddles, twiddle_stride, inverse)
+}
+
+/// one `Complex<f64>`
+#[derive(Clone, Copy)]
+struct NeonF64(float64x2_t);
+
+impl ComplexVector for NeonF64 {
+    type Float = f64;
+    const WIDTH: usize = 1;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const Complex<f64>) -> NeonF64 {
+        NeonF64(vld1q_f64(ptr as *const f64))
+    }
+
+    #[inline(always)]
+    unsafe fn gather(ptr: *const Complex<f64>, _stride: usize) -> NeonF64 {
+        NeonF64::load(ptr)
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut Complex<f64>) {
+        vst1q_f64(ptr as *mut f64, self.0)
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: NeonF64) -> NeonF64 {
+        NeonF64(vaddq_f64(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn sub(self, other: NeonF64) -> NeonF64 {
+        NeonF64(vsubq_f64(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn mul(self, other: NeonF64) -> NeonF64 {
+        // [a.re * b.re, a.re * b.im] + [-(a.im * b.im), a.im * b.re]
+        let re = vdupq_laneq_f64::<0>(self.0);
+        let im = vdupq_laneq_f64::<1>(self.0);
+        let swapped = vextq_f64::<1>(other.0, other.0);
+        let cross = vmulq_f64(vmulq_f64(im, swapped), f64_pair(-1.0, 1.0));
+        NeonF64(vaddq_f64(vmulq_f64(re, other.0), cross))
+    }
+
+    #[inline(always)]
+    unsafe fn half(self) -> NeonF64 {
+        NeonF64(vmulq_n_f64(self.0, 0.5))
+    }
+
+    #[inline(always)]
+    unsafe fn rotate(self, inverse: bool) -> NeonF64 {
+        let swapped = vextq_f64::<1>(self.0, self.0);
+        let sign = if inverse { f64_pair(-1.0, 1.0) } else { f64_pair(1.0, -1.0) };
+        NeonF64(vmulq_f64(swapped, sign))
+    }
+}
+
+/// two `Complex<f32>`
+#[derive(Clone, Copy)]
+struct NeonF32(float32x4_t);
+
+impl ComplexVector for NeonF32 {
+    type Float = f32;
+    const WIDTH: usize = 2;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const Complex<f32>) -> NeonF32 {
+        NeonF32(vld1q_f32(ptr as *const f32))
+    }
+
+    #[inline(always)]
+    unsafe fn gather(ptr: *const Complex<f32>, stride: usize) -> NeonF32 {
+        if stride == 1 {
+            return NeonF32::load(ptr);
+        }
+        let (a, b) = (*ptr, *ptr.add(stride));
+        NeonF32(vld1q_f32([a.re, a.im, b.re, b.im].as_ptr()))
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut Complex<f32>) {
+        vst1q_f32(ptr as *mut f32, self.0)
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: NeonF32) -> NeonF32 {
+        NeonF32(vaddq_f32(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn sub(self, other: NeonF32) -> NeonF32 {
+        NeonF32(vsubq_f32(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn mul(self, other: NeonF32) -> NeonF32 {
+        let re = vtrn1q_f32(self.0, self.0);
+        let im = vtrn2q_f32(self.0, self.0);
+        let swapped = vrev64q_f32(other.0);
+        let cross = vmulq_f32(vmulq_f32(im, swapped), f32_pairs(-1.0, 1.0));
+        NeonF32(vaddq_f32(vmulq_f32(re, other.0), cross))
+    }
+
+    #[inline(always)]
+    unsafe fn half(self) -> NeonF32 {
+        NeonF32(vmulq_n_f32(self.0, 0.5))
+    }
+
+    #[inline(always)]
+    unsafe fn rotate(self, inverse: bool) -> NeonF32 {
+        let swapped = vrev64q_f32(self.0);
+        let sign = if inverse { f32_pairs(-1.0, 1.0) } else { f32_pairs(1.0, -1.0) };
+        NeonF32(vmulq_f32(swapped, sign))
+    }
+}
+
+/// [re, im]
+#[inline(always)]
+unsafe fn f64_pair(re: f64, im: f64) -> float64x2_t {
+    vld1q_f64([re, im].as_ptr())
+}
+
+/// [re, im, re, im]
+#[inline(always)]
+unsafe fn f32_pairs(re: f32, im: f32) -> float32x4_t {
+    vld1q_f32([re, im, re, im].as_ptr())
+}
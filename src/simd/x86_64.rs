@@ -0,0 +1,536 @@
+//! SSE2, AVX and AVX2+FMA kernels
+//!
+//! SSE2 is part of x86_64 itself, so it's the floor; AVX and FMA are checked
+//! for at runtime (std caches the answer after the first call)
+
+use std::arch::x86_64::*;
+use num::complex::Complex;
+
+use super::{radix2_pass, radix4_pass, ComplexVector};
+
+/// the best kernel set this cpu supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Sse2,
+    Avx,
+    Fma,
+}
+
+fn level() -> Level {
+    if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+        Level::Fma
+    } else if is_x86_feature_detected!("avx") {
+        Level::Avx
+    } else {
+        Level::Sse2
+    }
+}
+
+pub(super) unsafe fn radix2_f64(
+    y_e: &mut [Complex<f64>], y_o: &mut [Complex<f64>],
+    twiddles: &[Complex<f64>], twiddle_stride: usize, inverse: bool
+) {
+    match level() {
+        Level::Fma => radix2_fma_f64(y_e, y_o, twiddles, twiddle_stride, inverse),
+        Level::Avx => radix2_avx_f64(y_e, y_o, twiddles, twiddle_stride, inverse),
+        Level::Sse2 => radix2_sse2_f64(y_e, y_o, twiddles, twiddle_stride, inverse),
+    }
+}
+
+pub(super) unsafe fn radix2_f32(
+    y_e: &mut [Complex<f32>], y_o: &mut [Complex<f32>],
+    twiddles: &[Complex<f32>], twiddle_stride: usize, inverse: bool
+) {
+    match level() {
+        Level::Fma => radix2_fma_f32(y_e, y_o, twiddles, twiddle_stride, inverse),
+        Level::Avx => radix2_avx_f32(y_e, y_o, twiddles, twiddle_stride, inverse),
+        Level::Sse2 => radix2_sse2_f32(y_e, y_o, twiddles, twiddle_stride, inverse),
+    }
+}
+
+pub(super) unsafe fn radix4_f64(
+    data: &mut [Complex<f64>], twiddles: &[Complex<f64>], twiddle_stride: usize, inverse: bool
+) {
+    match level() {
+        Level::Fma => radix4_fma_f64(data, twiddles, twiddle_stride, inverse),
+        Level::Avx => radix4_avx_f64(data, twiddles, twiddle_stride, inverse),
+        Level::Sse2 => radix4_sse2_f64(data, twiddles, twiddle_stride, inverse),
+    }
+}
+
+pub(super) unsafe fn radix4_f32(
+    data: &mut [Complex<f32>], twiddles: &[Complex<f32>], twiddle_stride: usize, inverse: bool
+) {
+    match level() {
+        Level::Fma => radix4_fma_f32(data, twiddles, twiddle_stride, inverse),
+        Level::Avx => radix4_avx_f32(data, twiddles, twiddle_stride, inverse),
+        Level::Sse2 => radix4_sse2_f32(data, twiddles, twiddle_stride, inverse),
+    }
+}
+
+/// compile `radix2_pass` and `radix4_pass` for one vector type with its
+/// target features enabled, so the intrinsics inline into the loops
+macro_rules! kernels {
+    ($radix2:ident, $radix4:ident, $vector:ty, $float:ty, $features:literal) => {
+        #[target_feature(enable = $features)]
+        unsafe fn $radix2(
+            y_e: &mut [Complex<$float>], y_o: &mut [Complex<$float>],
+            twiddles: &[Complex<$float>], twiddle_stride: usize, inverse: bool
+        ) {
+            radix2_pass::<$vector>(y_e, y_o, twiddles, twiddle_stride, inverse)
+        }
+
+        #[target_feature(enable = $features)]
+        unsafe fn $radix4(
+            data: &mut [Complex<$float>], twiddles: &[Complex<$float>],
+            twiddle_stride: usize, inverse: bool
+        ) {
+            radix4_pass::<$vector>(data, twiddles, twiddle_stride, inverse)
+        }
+    };
+}
+
+kernels!(radix2_sse2_f64, radix4_sse2_f64, Sse2F64, f64, "sse2");
+kernels!(radix2_avx_f64, radix4_avx_f64, AvxF64, f64, "avx");
+kernels!(radix2_fma_f64, radix4_fma_f64, FmaF64, f64, "avx2,fma");
+kernels!(radix2_sse2_f32, radix4_sse2_f32, Sse2F32, f32, "sse2");
+kernels!(radix2_avx_f32, radix4_avx_f32, AvxF32, f32, "avx");
+kernels!(radix2_fma_f32, radix4_fma_f32, FmaF32, f32, "avx2,fma");
+
+/// one `Complex<f64>`
+#[derive(Clone, Copy)]
+struct Sse2F64(__m128d);
+
+impl ComplexVector for Sse2F64 {
+    type Float = f64;
+    const WIDTH: usize = 1;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const Complex<f64>) -> Sse2F64 {
+        Sse2F64(_mm_loadu_pd(ptr as *const f64))
+    }
+
+    #[inline(always)]
+    unsafe fn gather(ptr: *const Complex<f64>, _stride: usize) -> Sse2F64 {
+        Sse2F64::load(ptr)
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut Complex<f64>) {
+        _mm_storeu_pd(ptr as *mut f64, self.0)
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: Sse2F64) -> Sse2F64 {
+        Sse2F64(_mm_add_pd(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn sub(self, other: Sse2F64) -> Sse2F64 {
+        Sse2F64(_mm_sub_pd(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn mul(self, other: Sse2F64) -> Sse2F64 {
+        // [a.re * b.re, a.re * b.im] + [-(a.im * b.im), a.im * b.re]
+        let re = _mm_unpacklo_pd(self.0, self.0);
+        let im = _mm_unpackhi_pd(self.0, self.0);
+        let swapped = _mm_shuffle_pd(other.0, other.0, 0b01);
+        let cross = _mm_xor_pd(_mm_mul_pd(im, swapped), _mm_setr_pd(-0.0, 0.0));
+        Sse2F64(_mm_add_pd(_mm_mul_pd(re, other.0), cross))
+    }
+
+    #[inline(always)]
+    unsafe fn half(self) -> Sse2F64 {
+        Sse2F64(_mm_mul_pd(self.0, _mm_set1_pd(0.5)))
+    }
+
+    #[inline(always)]
+    unsafe fn rotate(self, inverse: bool) -> Sse2F64 {
+        let swapped = _mm_shuffle_pd(self.0, self.0, 0b01);
+        let sign = if inverse { _mm_setr_pd(-0.0, 0.0) } else { _mm_setr_pd(0.0, -0.0) };
+        Sse2F64(_mm_xor_pd(swapped, sign))
+    }
+}
+
+/// two `Complex<f64>`
+#[derive(Clone, Copy)]
+struct AvxF64(__m256d);
+
+impl AvxF64 {
+    /// the real parts, the imaginary parts, and `other` with re and im swapped
+    #[inline(always)]
+    unsafe fn split(self, other: AvxF64) -> (__m256d, __m256d, __m256d) {
+        (
+            _mm256_movedup_pd(self.0),
+            _mm256_permute_pd(self.0, 0b1111),
+            _mm256_permute_pd(other.0, 0b0101),
+        )
+    }
+}
+
+impl ComplexVector for AvxF64 {
+    type Float = f64;
+    const WIDTH: usize = 2;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const Complex<f64>) -> AvxF64 {
+        AvxF64(_mm256_loadu_pd(ptr as *const f64))
+    }
+
+    #[inline(always)]
+    unsafe fn gather(ptr: *const Complex<f64>, stride: usize) -> AvxF64 {
+        if stride == 1 {
+            return AvxF64::load(ptr);
+        }
+        let (a, b) = (*ptr, *ptr.add(stride));
+        AvxF64(_mm256_setr_pd(a.re, a.im, b.re, b.im))
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut Complex<f64>) {
+        _mm256_storeu_pd(ptr as *mut f64, self.0)
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: AvxF64) -> AvxF64 {
+        AvxF64(_mm256_add_pd(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn sub(self, other: AvxF64) -> AvxF64 {
+        AvxF64(_mm256_sub_pd(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn mul(self, other: AvxF64) -> AvxF64 {
+        let (re, im, swapped) = self.split(other);
+        AvxF64(_mm256_addsub_pd(_mm256_mul_pd(re, other.0), _mm256_mul_pd(im, swapped)))
+    }
+
+    #[inline(always)]
+    unsafe fn half(self) -> AvxF64 {
+        AvxF64(_mm256_mul_pd(self.0, _mm256_set1_pd(0.5)))
+    }
+
+    #[inline(always)]
+    unsafe fn rotate(self, inverse: bool) -> AvxF64 {
+        let swapped = _mm256_permute_pd(self.0, 0b0101);
+        let sign = if inverse {
+            _mm256_setr_pd(-0.0, 0.0, -0.0, 0.0)
+        } else {
+            _mm256_setr_pd(0.0, -0.0, 0.0, -0.0)
+        };
+        AvxF64(_mm256_xor_pd(swapped, sign))
+    }
+}
+
+/// `AvxF64` with a fused multiply-add in the complex product
+#[derive(Clone, Copy)]
+struct FmaF64(AvxF64);
+
+impl ComplexVector for FmaF64 {
+    type Float = f64;
+    const WIDTH: usize = 2;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const Complex<f64>) -> FmaF64 {
+        FmaF64(AvxF64::load(ptr))
+    }
+
+    #[inline(always)]
+    unsafe fn gather(ptr: *const Complex<f64>, stride: usize) -> FmaF64 {
+        FmaF64(AvxF64::gather(ptr, stride))
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut Complex<f64>) {
+        self.0.store(ptr)
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: FmaF64) -> FmaF64 {
+        FmaF64(self.0.add(other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn sub(self, other: FmaF64) -> FmaF64 {
+        FmaF64(self.0.sub(other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn mul(self, other: FmaF64) -> FmaF64 {
+        let (re, im, swapped) = self.0.split(other.0);
+        FmaF64(AvxF64(_mm256_fmaddsub_pd(re, other.0.0, _mm256_mul_pd(im, swapped))))
+    }
+
+    #[inline(always)]
+    unsafe fn half(self) -> FmaF64 {
+        FmaF64(self.0.half())
+    }
+
+    #[inline(always)]
+    unsafe fn rotate(self, inverse: bool) -> FmaF64 {
+        FmaF64(self.0.rotate(inverse))
+    }
+}
+
+/// two `Complex<f32>`
+#[derive(Clone, Copy)]
+struct Sse2F32(__m128);
+
+impl ComplexVector for Sse2F32 {
+    type Float = f32;
+    const WIDTH: usize = 2;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const Complex<f32>) -> Sse2F32 {
+        Sse2F32(_mm_loadu_ps(ptr as *const f32))
+    }
+
+    #[inline(always)]
+    unsafe fn gather(ptr: *const Complex<f32>, stride: usize) -> Sse2F32 {
+        if stride == 1 {
+            return Sse2F32::load(ptr);
+        }
+        let (a, b) = (*ptr, *ptr.add(stride));
+        Sse2F32(_mm_setr_ps(a.re, a.im, b.re, b.im))
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut Complex<f32>) {
+        _mm_storeu_ps(ptr as *mut f32, self.0)
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: Sse2F32) -> Sse2F32 {
+        Sse2F32(_mm_add_ps(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn sub(self, other: Sse2F32) -> Sse2F32 {
+        Sse2F32(_mm_sub_ps(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn mul(self, other: Sse2F32) -> Sse2F32 {
+        let re = _mm_shuffle_ps(self.0, self.0, 0b10_10_00_00);
+        let im = _mm_shuffle_ps(self.0, self.0, 0b11_11_01_01);
+        let swapped = _mm_shuffle_ps(other.0, other.0, 0b10_11_00_01);
+        let cross = _mm_xor_ps(_mm_mul_ps(im, swapped), _mm_setr_ps(-0.0, 0.0, -0.0, 0.0));
+        Sse2F32(_mm_add_ps(_mm_mul_ps(re, other.0), cross))
+    }
+
+    #[inline(always)]
+    unsafe fn half(self) -> Sse2F32 {
+        Sse2F32(_mm_mul_ps(self.0, _mm_set1_ps(0.5)))
+    }
+
+    #[inline(always)]
+    unsafe fn rotate(self, inverse: bool) -> Sse2F32 {
+        let swapped = _mm_shuffle_ps(self.0, self.0, 0b10_11_00_01);
+        let sign = if inverse {
+            _mm_setr_ps(-0.0, 0.0, -0.0, 0.0)
+        } else {
+            _mm_setr_ps(0.0, -0.0, 0.0, -0.0)
+        };
+        Sse2F32(_mm_xor_ps(swapped, sign))
+    }
+}
+
+/// four `Complex<f32>`
+#[derive(Clone, Copy)]
+struct AvxF32(__m256);
+
+impl AvxF32 {
+    /// the real parts, the imaginary parts, and `other` with re and im swapped
+    #[inline(always)]
+    unsafe fn split(self, other: AvxF32) -> (__m256, __m256, __m256) {
+        (
+            _mm256_moveldup_ps(self.0),
+            _mm256_movehdup_ps(self.0),
+            _mm256_permute_ps(other.0, 0b10_11_00_01),
+        )
+    }
+}
+
+impl ComplexVector for AvxF32 {
+    type Float = f32;
+    const WIDTH: usize = 4;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const Complex<f32>) -> AvxF32 {
+        AvxF32(_mm256_loadu_ps(ptr as *const f32))
+    }
+
+    #[inline(always)]
+    unsafe fn gather(ptr: *const Complex<f32>, stride: usize) -> AvxF32 {
+        if stride == 1 {
+            return AvxF32::load(ptr);
+        }
+        let (a, b) = (*ptr, *ptr.add(stride));
+        let (c, d) = (*ptr.add(2 * stride), *ptr.add(3 * stride));
+        AvxF32(_mm256_setr_ps(a.re, a.im, b.re, b.im, c.re, c.im, d.re, d.im))
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut Complex<f32>) {
+        _mm256_storeu_ps(ptr as *mut f32, self.0)
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: AvxF32) -> AvxF32 {
+        AvxF32(_mm256_add_ps(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn sub(self, other: AvxF32) -> AvxF32 {
+        AvxF32(_mm256_sub_ps(self.0, other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn mul(self, other: AvxF32) -> AvxF32 {
+        let (re, im, swapped) = self.split(other);
+        AvxF32(_mm256_addsub_ps(_mm256_mul_ps(re, other.0), _mm256_mul_ps(im, swapped)))
+    }
+
+    #[inline(always)]
+    unsafe fn half(self) -> AvxF32 {
+        AvxF32(_mm256_mul_ps(self.0, _mm256_set1_ps(0.5)))
+    }
+
+    #[inline(always)]
+    unsafe fn rotate(self, inverse: bool) -> AvxF32 {
+        let swapped = _mm256_permute_ps(self.0, 0b10_11_00_01);
+        let sign = if inverse {
+            _mm256_setr_ps(-0.0, 0.0, -0.0, 0.0, -0.0, 0.0, -0.0, 0.0)
+        } else {
+            _mm256_setr_ps(0.0, -0.0, 0.0, -0.0, 0.0, -0.0, 0.0, -0.0)
+        };
+        AvxF32(_mm256_xor_ps(swapped, sign))
+    }
+}
+
+/// `AvxF32` with a fused multiply-add in the complex product
+#[derive(Clone, Copy)]
+struct FmaF32(AvxF32);
+
+impl ComplexVector for FmaF32 {
+    type Float = f32;
+    const WIDTH: usize = 4;
+
+    #[inline(always)]
+    unsafe fn load(ptr: *const Complex<f32>) -> FmaF32 {
+        FmaF32(AvxF32::load(ptr))
+    }
+
+    #[inline(always)]
+    unsafe fn gather(ptr: *const Complex<f32>, stride: usize) -> FmaF32 {
+        FmaF32(AvxF32::gather(ptr, stride))
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, ptr: *mut Complex<f32>) {
+        self.0.store(ptr)
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: FmaF32) -> FmaF32 {
+        FmaF32(self.0.add(other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn sub(self, other: FmaF32) -> FmaF32 {
+        FmaF32(self.0.sub(other.0))
+    }
+
+    #[inline(always)]
+    unsafe fn mul(self, other: FmaF32) -> FmaF32 {
+        let (re, im, swapped) = self.0.split(other.0);
+        FmaF32(AvxF32(_mm256_fmaddsub_ps(re, other.0.0, _mm256_mul_ps(im, swapped))))
+    }
+
+    #[inline(always)]
+    unsafe fn half(self) -> FmaF32 {
+        FmaF32(self.0.half())
+    }
+
+    #[inline(always)]
+    unsafe fn rotate(self, inverse: bool) -> FmaF32 {
+        FmaF32(self.0.rotate(inverse))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simd::tests::signal;
+    use crate::simd::{radix2_scalar, radix4_scalar};
+
+    #[test]
+    fn kernels_match_scalar_test() {
+        let twiddles = signal::<f64>(64, 1);
+        let twiddles_f32 = signal::<f32>(64, 1);
+        for inverse in [false, true] {
+            let (mut e, mut o) = (signal::<f64>(13, 2), signal::<f64>(13, 3));
+            radix2_scalar(&mut e, &mut o, &twiddles, 3, inverse);
+            let (mut e_f32, mut o_f32) = (signal::<f32>(13, 2), signal::<f32>(13, 3));
+            radix2_scalar(&mut e_f32, &mut o_f32, &twiddles_f32, 3, inverse);
+            let mut data = signal::<f64>(44, 4);
+            radix4_scalar(&mut data, &twiddles, 1, inverse);
+            let mut data_f32 = signal::<f32>(44, 4);
+            radix4_scalar(&mut data_f32, &twiddles_f32, 1, inverse);
+
+            // SSE2 is always there on x86_64
+            let check = |level: Level| unsafe {
+                let (mut a, mut b) = (signal::<f64>(13, 2), signal::<f64>(13, 3));
+                let (mut a_f32, mut b_f32) = (signal::<f32>(13, 2), signal::<f32>(13, 3));
+                let mut d = signal::<f64>(44, 4);
+                let mut d_f32 = signal::<f32>(44, 4);
+                match level {
+                    Level::Sse2 => {
+                        radix2_sse2_f64(&mut a, &mut b, &twiddles, 3, inverse);
+                        radix2_sse2_f32(&mut a_f32, &mut b_f32, &twiddles_f32, 3, inverse);
+                        radix4_sse2_f64(&mut d, &twiddles, 1, inverse);
+                        radix4_sse2_f32(&mut d_f32, &twiddles_f32, 1, inverse);
+                    }
+                    Level::Avx => {
+                        radix2_avx_f64(&mut a, &mut b, &twiddles, 3, inverse);
+                        radix2_avx_f32(&mut a_f32, &mut b_f32, &twiddles_f32, 3, inverse);
+                        radix4_avx_f64(&mut d, &twiddles, 1, inverse);
+                        radix4_avx_f32(&mut d_f32, &twiddles_f32, 1, inverse);
+                    }
+                    Level::Fma => {
+                        radix2_fma_f64(&mut a, &mut b, &twiddles, 3, inverse);
+                        radix2_fma_f32(&mut a_f32, &mut b_f32, &twiddles_f32, 3, inverse);
+                        radix4_fma_f64(&mut d, &twiddles, 1, inverse);
+                        radix4_fma_f32(&mut d_f32, &twiddles_f32, 1, inverse);
+                    }
+                }
+                (a, b, a_f32, b_f32, d, d_f32)
+            };
+
+            // SSE2 and AVX round exactly like the scalar loops
+            let mut levels = vec![Level::Sse2];
+            if is_x86_feature_detected!("avx") {
+                levels.push(Level::Avx);
+            }
+            for level in levels {
+                assert_eq!(check(level), (
+                    e.clone(), o.clone(), e_f32.clone(), o_f32.clone(), data.clone(), data_f32.clone()
+                ));
+            }
+
+            if level() == Level::Fma {
+                let (a, b, a_f32, b_f32, d, d_f32) = check(Level::Fma);
+                let close = |x: &[Complex<f64>], y: &[Complex<f64>]| x.iter().zip(y.iter())
+                    .all(|(x, y)| (x - y).norm() < 1e-12);
+                let close_f32 = |x: &[Complex<f32>], y: &[Complex<f32>]| x.iter().zip(y.iter())
+                    .all(|(x, y)| (x - y).norm() < 1e-4);
+                assert!(close(&a, &e) && close(&b, &o) && close(&d, &data));
+                assert!(close_f32(&a_f32, &e_f32) && close_f32(&b_f32, &o_f32));
+                assert!(close_f32(&d_f32, &data_f32));
+            }
+        }
+    }
+}
@@ -0,0 +1,303 @@
+//! spectrograms
+//!
+//! a spectrogram is the magnitude of an stft, scaled like
+//! `amplitude_spectrum` so a sinusoid of amplitude A reads A in every frame
+//! it spans. `render_spectrogram` draws one as a colormapped PNG with plotters.
+
+use std::path::Path;
+
+use plotters::prelude::*;
+
+use crate::error::FftError;
+use crate::float::FftFloat;
+use crate::spectrum::folded;
+use crate::stft::StftConfig;
+
+/// a magnitude (or, after `to_decibels`, dB) time-frequency matrix, stored
+/// row-major so frame m, bin k is `data[m * bins + k]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrogram<T: FftFloat> {
+    /// one row of `bins` values per frame
+    pub data: Vec<T>,
+    /// the number of frames (rows)
+    pub frames: usize,
+    /// the number of bins per frame (columns)
+    pub bins: usize,
+    /// the time of the centre of each frame, in seconds
+    pub times: Vec<T>,
+    /// the frequency of each bin, in Hz
+    pub frequencies: Vec<T>,
+}
+
+impl<T: FftFloat> Spectrogram<T> {
+    /// the values of frame `m`
+    pub fn frame(&self, m: usize) -> &[T] {
+        &self.data[m * self.bins..(m + 1) * self.bins]
+    }
+
+    /// the same spectrogram in dB (20 log10), with everything more than
+    /// `dynamic_range` dB below the loudest value raised to that floor so
+    /// silence doesn't come out as -inf
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fft_rust::{StftConfig, Window};
+    ///
+    /// let signal = vec![0f64; 64];
+    /// let spectrogram = fft_rust::spectrogram(&signal, 8000., &StftConfig::new(Window::Hann, 16, 4));
+    /// assert!(spectrogram.to_decibels(120.).data.iter().all(|x| x.is_finite()));
+    /// ```
+    pub fn to_decibels(&self, dynamic_range: T) -> Spectrogram<T> {
+        let twenty = T::from_f64(20.0);
+        let decibels: Vec<T> = self.data.iter().map(|x| twenty * x.log10()).collect();
+        let peak = decibels.iter().copied().fold(T::neg_infinity(), T::max);
+        // all silence has no loudest value; call it 0 dB
+        let floor = if peak.is_finite() { peak - dynamic_range } else { -dynamic_range };
+        Spectrogram { data: decibels.iter().map(|x| x.max(floor)).collect(), ..self.clone() }
+    }
+}
+
+/// the spectrogram of a real signal sampled at `sample_rate`, framed as in
+/// `stft`; panics in the same cases as `stft`
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{StftConfig, Window};
+///
+/// // a 0.5 amplitude, 1 kHz tone sampled at 8 kHz
+/// let signal: Vec<f64> = (0..8000).map(
+///     |n| 0.5 * (2.0 * std::f64::consts::PI * 1000.0 * n as f64 / 8000.0).sin()
+/// ).collect();
+/// let spectrogram = fft_rust::spectrogram(&signal, 8000., &StftConfig::new(Window::Hann, 256, 64));
+/// assert_eq!(spectrogram.frequencies[32], 1000.);
+/// assert!((spectrogram.frame(60)[32] - 0.5).abs() < 1e-9);
+/// ```
+pub fn spectrogram<T: FftFloat>(
+    signal: &[T], sample_rate: T, config: &StftConfig<T>
+) -> Spectrogram<T> {
+    let stft = crate::stft(signal, config);
+    let gain: T = config.window.iter().copied().sum();
+    let two = T::from_f64(2.0);
+    let data = stft.data.chunks_exact(stft.bins).flat_map(|frame| {
+        frame.iter().enumerate().map(move |(k, x)| {
+            let magnitude = x.norm() / gain;
+            if folded(k, config.fft_len) { magnitude * two } else { magnitude }
+        })
+    }).collect();
+
+    Spectrogram {
+        data,
+        frames: stft.frames,
+        bins: stft.bins,
+        times: stft.times(sample_rate),
+        frequencies: stft.frequencies(sample_rate),
+    }
+}
+
+/// the colors `render_spectrogram` maps values to, from lowest to highest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colormap {
+    /// dark blue through green to yellow; perceptually uniform
+    #[default]
+    Viridis,
+    /// black through purple and orange to pale yellow; perceptually uniform
+    Magma,
+    /// black to white
+    Grayscale,
+}
+
+impl Colormap {
+    /// the color for `x` between 0 and 1, interpolated between 9 evenly
+    /// spaced stops taken from matplotlib's colormaps
+    pub fn color(&self, x: f64) -> RGBColor {
+        const VIRIDIS: [(u8, u8, u8); 9] = [
+            (68, 1, 84), (72, 40, 120), (62, 73, 137), (49, 104, 142), (38, 130, 142),
+            (31, 158, 137), (53, 183, 121), (110, 206, 88), (253, 231, 37),
+        ];
+        const MAGMA: [(u8, u8, u8); 9] = [
+            (0, 0, 4), (28, 16, 68), (79, 18, 123), (129, 37, 129), (181, 54, 122),
+            (229, 80, 100), (251, 136, 97), (254, 194, 135), (252, 253, 191),
+        ];
+        let stops = match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Grayscale => &[(0, 0, 0), (255, 255, 255)][..],
+        };
+
+        let position = x.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+        let low = (position.floor() as usize).min(stops.len() - 2);
+        let fraction = position - low as f64;
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+        let (a, b) = (stops[low], stops[low + 1]);
+        RGBColor(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+    }
+}
+
+/// how `render_spectrogram` draws a chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    /// the image width in pixels
+    pub width: u32,
+    /// the image height in pixels
+    pub height: u32,
+    /// how values map to colors
+    pub colormap: Colormap,
+    /// the caption above the chart
+    pub title: String,
+    /// the top of the frequency axis in Hz; `None` goes up to Nyquist
+    pub max_frequency: Option<f64>,
+}
+
+impl Default for RenderOptions {
+    /// a 1024 x 768 viridis chart up to Nyquist
+    fn default() -> RenderOptions {
+        RenderOptions {
+            width: 1024,
+            height: 768,
+            colormap: Colormap::Viridis,
+            title: "Spectrogram".to_string(),
+            max_frequency: None,
+        }
+    }
+}
+
+/// draw `spectrogram` to a PNG at `path`, time along the bottom and
+/// frequency up the side, with colors spread from its lowest to its highest
+/// value; usually given `to_decibels` output
+///
+/// # Example
+///
+/// ```no_run
+/// use fft_rust::{RenderOptions, StftConfig, Window};
+///
+/// # let signal = vec![0f64; 8000];
+/// let spectrogram = fft_rust::spectrogram(&signal, 8000., &StftConfig::new(Window::Hann, 256, 64));
+/// fft_rust::render_spectrogram(
+///     &spectrogram.to_decibels(80.), "spectrogram.png", &RenderOptions::default()
+/// ).unwrap();
+/// ```
+pub fn render_spectrogram<T: FftFloat, P: AsRef<Path>>(
+    spectrogram: &Spectrogram<T>, path: P, options: &RenderOptions
+) -> Result<(), FftError> {
+    let (frames, bins) = (spectrogram.frames, spectrogram.bins);
+    assert_eq!(
+        spectrogram.data.len(), frames * bins,
+        "expected {} frames of {} bins, found {} values", frames, bins, spectrogram.data.len()
+    );
+    if frames == 0 || bins == 0 {
+        return Err(FftError::EmptyInput);
+    }
+    let float = |x: T| x.to_f64().unwrap_or(f64::NAN);
+    let times: Vec<f64> = spectrogram.times.iter().map(|t| float(*t)).collect();
+    let frequencies: Vec<f64> = spectrogram.frequencies.iter().map(|f| float(*f)).collect();
+    let (low, high) = spectrogram.data.iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY), |(low, high), x| (low.min(float(*x)), high.max(float(*x)))
+    );
+
+    // a single frame or bin still gets an axis with some width
+    let end_time = if frames > 1 { times[frames - 1] } else { times[0] + 1.0 };
+    let nyquist = frequencies[bins - 1];
+    let top = options.max_frequency.unwrap_or(nyquist).min(nyquist);
+    let top = if top > 0.0 { top } else { 1.0 };
+
+    let root = BitMapBackend::new(path.as_ref(), (options.width, options.height)).into_drawing_area();
+    let render = |err: &dyn std::error::Error| FftError::Render(err.to_string());
+    root.fill(&WHITE).map_err(|err| render(&err))?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(5)
+        .margin_right(25)
+        .set_label_area_size(LabelAreaPosition::Left, 60)
+        .set_label_area_size(LabelAreaPosition::Bottom, 50)
+        .caption(&options.title, ("sans-serif", 40))
+        .build_cartesian_2d(times[0]..end_time, 0.0..top)
+        .map_err(|err| render(&err))?;
+    chart.configure_mesh()
+        .disable_mesh()
+        .x_desc("time (s)")
+        .y_desc("frequency (Hz)")
+        .x_label_formatter(&|v| format!("{:.2}", v))
+        .y_label_formatter(&|v| format!("{:.0}", v))
+        .draw()
+        .map_err(|err| render(&err))?;
+
+    // color every pixel of the plot with the nearest frame and bin
+    let area = chart.plotting_area().strip_coord_spec();
+    let (width, height) = area.dim_in_pixel();
+    let step = if bins > 1 { frequencies[1] } else { 1.0 };
+    for x in 0..width {
+        let time = times[0] + (end_time - times[0]) * (x as f64 + 0.5) / width as f64;
+        let frame = nearest(&times, time);
+        for y in 0..height {
+            let frequency = top * (1.0 - (y as f64 + 0.5) / height as f64);
+            let bin = ((frequency / step).round() as usize).min(bins - 1);
+            let value = float(spectrogram.data[frame * bins + bin]);
+            let scaled = if high > low { (value - low) / (high - low) } else { 0.0 };
+            area.draw_pixel((x as i32, y as i32), &options.colormap.color(scaled))
+                .map_err(|err| render(&err))?;
+        }
+    }
+
+    root.present().map_err(|err| render(&err))?;
+    Ok(())
+}
+
+/// the index of the value in ascending `values` closest to `x`
+fn nearest(values: &[f64], x: f64) -> usize {
+    let above = values.partition_point(|v| *v < x).min(values.len() - 1);
+    if above > 0 && x - values[above - 1] < values[above] - x { above - 1 } else { above }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::Window;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn spectrogram_test() {
+        // a tone that steps from 1 kHz to 2 kHz half way through
+        let fs = 8000.0;
+        let signal: Vec<f64> = (0..16000).map(|n| {
+            let f = if n < 8000 { 1000.0 } else { 2000.0 };
+            0.8 * (2.0 * PI * f * n as f64 / fs).cos()
+        }).collect();
+        let spectrogram = spectrogram(&signal, fs, &StftConfig::new(Window::Hann, 256, 128));
+        assert_eq!(spectrogram.times.len(), spectrogram.frames);
+        assert_eq!(spectrogram.frequencies.len(), spectrogram.bins);
+        let peak = |m: usize| {
+            let frame = spectrogram.frame(m);
+            let k = (0..frame.len()).fold(0, |best, k| if frame[k] > frame[best] { k } else { best });
+            (spectrogram.frequencies[k], frame[k])
+        };
+        let (frequency, amplitude) = peak(20);
+        assert_eq!(frequency, 1000.0);
+        assert!((amplitude - 0.8).abs() < 1e-9);
+        assert_eq!(peak(spectrogram.frames - 20).0, 2000.0);
+        assert!((spectrogram.times[20] - 20.0 * 128.0 / fs).abs() < 1e-12);
+
+        let decibels = spectrogram.to_decibels(60.0);
+        let loudest = decibels.data.iter().copied().fold(f64::MIN, f64::max);
+        assert!((loudest - 20.0 * 0.8f64.log10()).abs() < 1e-6);
+        assert!(decibels.data.iter().all(|x| *x >= loudest - 60.0));
+    }
+
+    #[test]
+    fn colormap_test() {
+        assert_eq!(Colormap::Viridis.color(0.0), RGBColor(68, 1, 84));
+        assert_eq!(Colormap::Viridis.color(1.0), RGBColor(253, 231, 37));
+        assert_eq!(Colormap::Magma.color(-3.0), RGBColor(0, 0, 4));
+        assert_eq!(Colormap::Grayscale.color(0.5), RGBColor(128, 128, 128));
+        assert_eq!(Colormap::Viridis.color(1.0 / 16.0), RGBColor(70, 21, 102));
+    }
+
+    #[test]
+    fn nearest_test() {
+        let values = [0.0, 1.0, 2.0, 3.0];
+        assert_eq!(nearest(&values, -1.0), 0);
+        assert_eq!(nearest(&values, 1.4), 1);
+        assert_eq!(nearest(&values, 1.6), 2);
+        assert_eq!(nearest(&values, 9.0), 3);
+    }
+}
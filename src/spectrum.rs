@@ -0,0 +1,208 @@
+//! one-sided spectra of real signals
+//!
+//! a real signal's spectrum is symmetric, so the negative frequencies are
+//! folded onto the positive ones: every bin except DC and Nyquist is doubled.
+//! dividing by the window's sum (N for no window) instead of the length makes
+//! a sinusoid's peak read as its amplitude whichever window was applied.
+
+use crate::float::FftFloat;
+
+/// what `amplitude_spectrum` and `power_spectrum` return; bin k of every
+/// field is the frequency `frequencies[k]`, from DC up to Nyquist
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum<T: FftFloat> {
+    /// the frequency of each bin, in the units of the sample rate
+    pub frequencies: Vec<T>,
+    /// peak amplitude (`amplitude_spectrum`) or power (`power_spectrum`) per bin
+    pub magnitudes: Vec<T>,
+    /// phase of each bin in radians, in (-pi, pi]
+    pub phases: Vec<T>,
+    /// `magnitudes` in decibels: 20 log10 for amplitudes, 10 log10 for powers
+    pub decibels: Vec<T>,
+}
+
+impl<T: FftFloat> Spectrum<T> {
+    /// the frequency and magnitude of the strongest bin
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let signal = [1f64, 0., -1., 0.];
+    /// assert_eq!(fft_rust::amplitude_spectrum(&signal, 4.).peak(), (1., 1.));
+    /// ```
+    pub fn peak(&self) -> (T, T) {
+        let k = (0..self.magnitudes.len())
+            .fold(0, |best, k| if self.magnitudes[k] > self.magnitudes[best] { k } else { best });
+        (self.frequencies[k], self.magnitudes[k])
+    }
+}
+
+/// the one-sided amplitude spectrum of a real signal sampled at
+/// `sample_rate`: a sinusoid of amplitude A on a bin shows up as A
+///
+/// the same as the MATLAB recipe of dividing the fft by N, keeping the first
+/// N/2 + 1 bins and doubling all but DC and Nyquist; panics if `signal` is empty
+///
+/// # Example
+///
+/// ```
+/// // 0.5 + 2 cos(2 pi t / 4), sampled at 8 Hz: DC of 0.5 and 2 at 2 Hz
+/// let signal = [2.5f64, 0.5, -1.5, 0.5, 2.5, 0.5, -1.5, 0.5];
+/// let spectrum = fft_rust::amplitude_spectrum(&signal, 8.);
+/// assert_eq!(spectrum.frequencies, vec![0., 1., 2., 3., 4.]);
+/// let rounded: Vec<f64> = spectrum.magnitudes.iter().map(|m| (m * 1e9).round() / 1e9).collect();
+/// assert_eq!(rounded, vec![0.5, 0., 2., 0., 0.]);
+/// ```
+pub fn amplitude_spectrum<T: FftFloat>(signal: &[T], sample_rate: T) -> Spectrum<T> {
+    spectrum(signal, sample_rate, None, false)
+}
+
+/// `amplitude_spectrum` of `signal` multiplied by `window`; the amplitudes
+/// are divided by the window's coherent gain so they read the same as
+/// without a window
+///
+/// panics if `window` isn't the same length as `signal`
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{Symmetry, Window};
+///
+/// // a Hann window halves the amplitude, which the scaling puts back
+/// let signal: Vec<f64> = (0..16).map(|n| (2.0 * std::f64::consts::PI * n as f64 / 4.0).cos()).collect();
+/// let hann = fft_rust::window(Window::Hann, 16, Symmetry::Periodic);
+/// let spectrum = fft_rust::amplitude_spectrum_windowed(&signal, 16., &hann);
+/// assert!((spectrum.peak().0 - 4.).abs() < 1e-12);
+/// assert!((spectrum.peak().1 - 1.).abs() < 1e-12);
+/// ```
+pub fn amplitude_spectrum_windowed<T: FftFloat>(
+    signal: &[T], sample_rate: T, window: &[T]
+) -> Spectrum<T> {
+    spectrum(signal, sample_rate, Some(window), false)
+}
+
+/// the one-sided power spectrum of a real signal sampled at `sample_rate`:
+/// a sinusoid of amplitude A on a bin shows up as A^2 / 2, its mean power
+///
+/// panics if `signal` is empty
+///
+/// # Example
+///
+/// ```
+/// let signal = [2.5f64, 0.5, -1.5, 0.5, 2.5, 0.5, -1.5, 0.5];
+/// let spectrum = fft_rust::power_spectrum(&signal, 8.);
+/// let rounded: Vec<f64> = spectrum.magnitudes.iter().map(|m| (m * 1e9).round() / 1e9).collect();
+/// assert_eq!(rounded, vec![0.25, 0., 2., 0., 0.]);
+/// ```
+pub fn power_spectrum<T: FftFloat>(signal: &[T], sample_rate: T) -> Spectrum<T> {
+    spectrum(signal, sample_rate, None, true)
+}
+
+/// `power_spectrum` of `signal` multiplied by `window`, scaled by the
+/// window's coherent gain squared so sinusoids read as A^2 / 2
+///
+/// panics if `window` isn't the same length as `signal`
+pub fn power_spectrum_windowed<T: FftFloat>(
+    signal: &[T], sample_rate: T, window: &[T]
+) -> Spectrum<T> {
+    spectrum(signal, sample_rate, Some(window), true)
+}
+
+fn spectrum<T: FftFloat>(
+    signal: &[T], sample_rate: T, window: Option<&[T]>, power: bool
+) -> Spectrum<T> {
+    let n = signal.len();
+    assert!(n > 0, "a spectrum needs at least one sample");
+    let (bins, gain) = match window {
+        Some(window) => {
+            assert_eq!(
+                window.len(), n,
+                "window has {} samples but the signal has {}", window.len(), n
+            );
+            let windowed: Vec<T> = signal.iter().zip(window.iter()).map(|(x, w)| *x * *w).collect();
+            (crate::rfft(&windowed), window.iter().copied().sum())
+        }
+        None => (crate::rfft(signal), T::from_usize(n)),
+    };
+
+    let two = T::from_f64(2.0);
+    let magnitudes: Vec<T> = bins.iter().enumerate().map(|(k, x)| {
+        let magnitude = if power { x.norm_sqr() / (gain * gain) } else { x.norm() / gain };
+        if folded(k, n) { magnitude * two } else { magnitude }
+    }).collect();
+    let decibels = magnitudes.iter().map(|m| {
+        let ratio = if power { T::from_f64(10.0) } else { T::from_f64(20.0) };
+        ratio * m.log10()
+    }).collect();
+
+    Spectrum {
+        frequencies: crate::rfftfreq(n, T::one() / sample_rate),
+        phases: bins.iter().map(|x| x.arg()).collect(),
+        magnitudes,
+        decibels,
+    }
+}
+
+/// true if bin `k` of a length-`n` real fft has a mirror image among the
+/// negative frequencies, i.e. it isn't DC or Nyquist
+pub(crate) fn folded(k: usize, n: usize) -> bool {
+    k != 0 && !(n.is_multiple_of(2) && k == n / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn tone(len: usize, cycles: f64, amplitude: f64, phase: f64) -> Vec<f64> {
+        (0..len).map(
+            |n| amplitude * (2.0 * PI * cycles * n as f64 / len as f64 + phase).cos()
+        ).collect()
+    }
+
+    #[test]
+    fn amplitude_spectrum_test() {
+        for len in [64, 65] {
+            let signal: Vec<f64> = tone(len, 5.0, 0.7, 0.3).iter()
+                .zip(tone(len, 12.0, 1.0, -1.0).iter())
+                .map(|(a, b)| a + b + 0.25)
+                .collect();
+            let spectrum = amplitude_spectrum(&signal, 128.0);
+            assert_eq!(spectrum.magnitudes.len(), len / 2 + 1);
+            assert!((spectrum.magnitudes[0] - 0.25).abs() < 1e-12);
+            assert!((spectrum.magnitudes[5] - 0.7).abs() < 1e-12);
+            assert!((spectrum.magnitudes[12] - 1.0).abs() < 1e-12);
+            assert!((spectrum.phases[5] - 0.3).abs() < 1e-12);
+            assert!((spectrum.phases[12] + 1.0).abs() < 1e-12);
+            assert!((spectrum.decibels[12]).abs() < 1e-9);
+            assert!((spectrum.frequencies[5] - 5.0 * 128.0 / len as f64).abs() < 1e-12);
+            assert_eq!(spectrum.peak().0, spectrum.frequencies[12]);
+        }
+    }
+
+    #[test]
+    fn nyquist_is_not_doubled_test() {
+        let signal = tone(8, 4.0, 1.0, 0.0);
+        let spectrum = amplitude_spectrum(&signal, 8.0);
+        assert!((spectrum.magnitudes[4] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn power_spectrum_test() {
+        let signal = tone(32, 3.0, 2.0, 0.0);
+        let spectrum = power_spectrum(&signal, 32.0);
+        assert!((spectrum.magnitudes[3] - 2.0).abs() < 1e-12);
+        assert!((spectrum.decibels[3] - 10.0 * 2f64.log10()).abs() < 1e-9);
+
+        // the window's gain is taken out of powers too
+        let window = crate::window(crate::Window::Blackman, 32, crate::Symmetry::Periodic);
+        let spectrum = power_spectrum_windowed(&signal, 32.0, &window);
+        assert!((spectrum.magnitudes[3] - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn window_length_mismatch_test() {
+        amplitude_spectrum_windowed(&[1f64, 2., 3.], 1.0, &[1., 1.]);
+    }
+}
@@ -0,0 +1,365 @@
+//! short-time Fourier transforms
+//!
+//! `stft` slides a window along a signal `hop` samples at a time and takes
+//! the spectrum of every windowed frame. the signal is padded with half a
+//! window of zeros at each end, so frame m is centred on sample m * hop and
+//! the first and last samples are covered as well as the middle.
+//!
+//! `istft` goes back by weighted overlap-add (WOLA): every frame is inverse
+//! transformed, windowed again and added in place, then each sample is
+//! divided by the sum of the squared windows that covered it. that inverts an
+//! unmodified stft exactly for any window that never leaves a sample
+//! uncovered. when frames are modified (filtered, masked, ...) the output is
+//! only free of amplitude ripple if the window is COLA at that hop, which is
+//! what `StftConfig::check_cola` checks.
+
+use num::complex::Complex;
+
+use crate::error::FftError;
+use crate::float::{zero, FftFloat};
+use crate::plan::Direction;
+use crate::window::{Symmetry, Window};
+
+/// how `stft` frames a signal
+#[derive(Debug, Clone, PartialEq)]
+pub struct StftConfig<T: FftFloat> {
+    /// applied to every frame; its length is the frame length
+    pub window: Vec<T>,
+    /// samples from the start of one frame to the start of the next
+    pub hop: usize,
+    /// each windowed frame is zero-padded to this length before its fft
+    pub fft_len: usize,
+}
+
+impl<T: FftFloat> StftConfig<T> {
+    /// frames of `len` samples weighted by a periodic `window`, every `hop`
+    /// samples, with an fft as long as a frame
+    pub fn new(window: Window, len: usize, hop: usize) -> StftConfig<T> {
+        StftConfig { window: crate::window(window, len, Symmetry::Periodic), hop, fft_len: len }
+    }
+
+    /// check that the squared window overlap-adds to a constant at this hop
+    /// (the COLA condition for WOLA), so modified frames resynthesize without
+    /// amplitude ripple; e.g. Hann is COLA at a quarter of its length but not
+    /// at half. a hop of zero is `FftError::ZeroHop`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use fft_rust::{FftError, StftConfig, Window};
+    ///
+    /// assert!(StftConfig::<f64>::new(Window::Hann, 512, 128).check_cola().is_ok());
+    /// assert!(matches!(
+    ///     StftConfig::<f64>::new(Window::Hann, 512, 256).check_cola(),
+    ///     Err(FftError::NotCola { .. })
+    /// ));
+    /// ```
+    pub fn check_cola(&self) -> Result<(), FftError> {
+        if self.hop == 0 {
+            return Err(FftError::ZeroHop);
+        }
+        // every sample gets the same squared-window sum as its position mod hop
+        let mut sums = vec![T::zero(); self.hop];
+        for (n, w) in self.window.iter().enumerate() {
+            sums[n % self.hop] += *w * *w;
+        }
+        let max = sums.iter().copied().fold(T::zero(), T::max);
+        let min = sums.iter().copied().fold(T::infinity(), T::min);
+        let ripple = if max > T::zero() { (max - min) / max } else { T::one() };
+        if ripple <= T::epsilon().sqrt() {
+            Ok(())
+        } else {
+            Err(FftError::NotCola { ripple: ripple.to_f64().unwrap_or(f64::NAN) })
+        }
+    }
+}
+
+/// the output of `stft`: `frames` spectra of `bins` bins each (DC to
+/// Nyquist), stored row-major so frame m, bin k is `data[m * bins + k]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stft<T: FftFloat> {
+    /// the time-frequency matrix, one row per frame
+    pub data: Vec<Complex<T>>,
+    /// the number of frames (rows)
+    pub frames: usize,
+    /// the number of bins per frame (columns), `fft_len / 2 + 1`
+    pub bins: usize,
+    /// the length of the signal that was transformed
+    pub signal_len: usize,
+    /// how the signal was framed
+    pub config: StftConfig<T>,
+}
+
+impl<T: FftFloat> Stft<T> {
+    /// the spectrum of frame `m`
+    pub fn frame(&self, m: usize) -> &[Complex<T>] {
+        &self.data[m * self.bins..(m + 1) * self.bins]
+    }
+
+    /// the spectrum of frame `m`, to modify before `istft`
+    pub fn frame_mut(&mut self, m: usize) -> &mut [Complex<T>] {
+        &mut self.data[m * self.bins..(m + 1) * self.bins]
+    }
+
+    /// the time of the centre of each frame, for a signal sampled at `sample_rate`
+    pub fn times(&self, sample_rate: T) -> Vec<T> {
+        (0..self.frames).map(|m| T::from_usize(m * self.config.hop) / sample_rate).collect()
+    }
+
+    /// the frequency of each bin, for a signal sampled at `sample_rate`
+    pub fn frequencies(&self, sample_rate: T) -> Vec<T> {
+        crate::rfftfreq(self.config.fft_len, T::one() / sample_rate)
+    }
+}
+
+/// the short-time Fourier transform of a real signal
+///
+/// panics if `signal` or the window is empty, the hop is zero, or `fft_len`
+/// is shorter than the window
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{StftConfig, Window};
+///
+/// // a 1 kHz tone sampled at 8 kHz, in 256-sample frames every 64 samples
+/// let signal: Vec<f64> = (0..8000).map(
+///     |n| (2.0 * std::f64::consts::PI * 1000.0 * n as f64 / 8000.0).sin()
+/// ).collect();
+/// let stft = fft_rust::stft(&signal, &StftConfig::new(Window::Hann, 256, 64));
+/// assert_eq!((stft.frames, stft.bins), (126, 129));
+///
+/// // every frame peaks at the 1 kHz bin
+/// let peak = stft.frame(60).iter().enumerate()
+///     .max_by(|a, b| a.1.norm().total_cmp(&b.1.norm())).unwrap().0;
+/// assert_eq!(stft.frequencies(8000.)[peak], 1000.);
+///
+/// let resynthesized = fft_rust::istft(&stft);
+/// assert!(resynthesized.iter().zip(&signal).all(|(a, b)| (a - b).abs() < 1e-9));
+/// ```
+pub fn stft<T: FftFloat>(signal: &[T], config: &StftConfig<T>) -> Stft<T> {
+    let (len, hop, fft_len) = (config.window.len(), config.hop, config.fft_len);
+    assert!(!signal.is_empty(), "an stft needs at least one sample");
+    assert!(len > 0, "an stft needs a window of at least one sample");
+    assert!(hop > 0, "an stft needs a hop of at least one sample");
+    assert!(fft_len >= len, "fft length {} is shorter than the {} sample window", fft_len, len);
+
+    // enough frames that the last one is centred at or past the last sample
+    let half = len / 2;
+    let frames = 1 + (signal.len() + 2 * half).saturating_sub(len).div_ceil(hop);
+    let bins = fft_len / 2 + 1;
+
+    let mut spectra = vec![zero(); frames * fft_len];
+    for (m, frame) in spectra.chunks_exact_mut(fft_len).enumerate() {
+        for (n, w) in config.window.iter().enumerate() {
+            // the padded signal starts `half` samples before the real one
+            if let Some(x) = (m * hop + n).checked_sub(half).and_then(|i| signal.get(i)) {
+                frame[n] = Complex::new(*x * *w, T::zero());
+            }
+        }
+    }
+    crate::batch(&mut spectra, fft_len, frames, Direction::Forward);
+
+    Stft {
+        data: spectra.chunks_exact(fft_len).flat_map(|frame| frame[..bins].iter().copied()).collect(),
+        frames,
+        bins,
+        signal_len: signal.len(),
+        config: config.clone(),
+    }
+}
+
+/// invert `stft` by weighted overlap-add, returning `stft.signal_len` samples
+///
+/// this doesn't check COLA, so frames modified under a non-COLA window and
+/// hop come back with amplitude ripple; `try_istft` refuses those. panics if
+/// some sample was only covered where the window is zero (e.g. a Hann window
+/// with a hop as long as the window), since it can't be recovered
+pub fn istft<T: FftFloat>(stft: &Stft<T>) -> Vec<T> {
+    let config = &stft.config;
+    let (len, hop, fft_len) = (config.window.len(), config.hop, config.fft_len);
+    assert_eq!(
+        stft.data.len(), stft.frames * stft.bins,
+        "expected {} frames of {} bins, found {} values", stft.frames, stft.bins, stft.data.len()
+    );
+    assert_eq!(stft.bins, fft_len / 2 + 1, "{} bins don't match an fft length of {}", stft.bins, fft_len);
+
+    // rebuild each frame's full, Hermitian spectrum and transform them all back
+    let mut frames = vec![zero(); stft.frames * fft_len];
+    for (frame, bins) in frames.chunks_exact_mut(fft_len).zip(stft.data.chunks_exact(stft.bins)) {
+        for (k, x) in frame.iter_mut().enumerate() {
+            *x = if k < stft.bins { bins[k] } else { bins[fft_len - k].conj() };
+        }
+    }
+    crate::batch(&mut frames, fft_len, stft.frames, Direction::Inverse);
+
+    let half = len / 2;
+    let mut output = vec![T::zero(); stft.signal_len];
+    let mut envelope = vec![T::zero(); stft.signal_len];
+    for (m, frame) in frames.chunks_exact(fft_len).enumerate() {
+        for (n, w) in config.window.iter().enumerate() {
+            if let Some(i) = (m * hop + n).checked_sub(half).filter(|i| *i < stft.signal_len) {
+                output[i] += frame[n].re * *w;
+                envelope[i] += *w * *w;
+            }
+        }
+    }
+
+    let peak = envelope.iter().copied().fold(T::zero(), T::max);
+    let floor = peak * T::from_f64(1e-10);
+    for (i, (x, e)) in output.iter_mut().zip(envelope.iter()).enumerate() {
+        assert!(*e > floor, "sample {} is only covered where the window is zero; use a smaller hop", i);
+        *x /= *e;
+    }
+    output
+}
+
+/// fallible version of `istft` that also insists on COLA: returns
+/// `FftError::NotCola` if the window doesn't overlap-add to a constant at
+/// the hop (so modified frames would come back with amplitude ripple), and
+/// `FftError::MismatchedShapes` instead of panicking if the data doesn't
+/// match the frames and bins
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{FftError, StftConfig, Window};
+///
+/// let signal: Vec<f64> = (0..1000).map(|n| (n as f64 * 0.05).sin()).collect();
+/// let stft = fft_rust::stft(&signal, &StftConfig::new(Window::Hann, 64, 16));
+/// assert_eq!(fft_rust::try_istft(&stft).unwrap().len(), 1000);
+///
+/// // Hann at half its length isn't COLA for WOLA
+/// let stft = fft_rust::stft(&signal, &StftConfig::new(Window::Hann, 64, 32));
+/// assert!(matches!(fft_rust::try_istft(&stft), Err(FftError::NotCola { .. })));
+/// ```
+pub fn try_istft<T: FftFloat>(stft: &Stft<T>) -> Result<Vec<T>, FftError> {
+    stft.config.check_cola()?;
+    let bins = stft.config.fft_len / 2 + 1;
+    if stft.bins != bins {
+        return Err(FftError::MismatchedShapes { expected: bins, found: stft.bins });
+    }
+    if stft.data.len() != stft.frames * stft.bins {
+        let expected = stft.frames * stft.bins;
+        return Err(FftError::MismatchedShapes { expected, found: stft.data.len() });
+    }
+    let config = &stft.config;
+    if config.window.len() > config.fft_len {
+        return Err(FftError::MismatchedShapes { expected: config.fft_len, found: config.window.len() });
+    }
+    Ok(istft(stft))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::noise;
+
+    #[test]
+    fn stft_matches_rfft_test() {
+        let input = noise(100, 1);
+        let config = StftConfig {
+            window: crate::window(Window::Hamming, 16, Symmetry::Periodic), hop: 5, fft_len: 20
+        };
+        let stft = stft(&input, &config);
+        assert_eq!(stft.bins, 11);
+        assert_eq!(stft.frames, 1 + 100usize.div_ceil(5));
+        for m in [0, 1, 10, stft.frames - 1] {
+            // frame m covers samples m * hop - 8 .. m * hop + 8, zero outside the signal
+            let mut frame = vec![0.0; 20];
+            for (n, (y, w)) in frame.iter_mut().zip(config.window.iter()).enumerate() {
+                if let Some(x) = (m * 5 + n).checked_sub(8).and_then(|i| input.get(i)) {
+                    *y = x * w;
+                }
+            }
+            for (a, b) in stft.frame(m).iter().zip(crate::rfft(&frame).iter()) {
+                assert!((a - b).norm() < 1e-9, "frame {} differs", m);
+            }
+        }
+        assert_eq!(stft.times(10.0)[3], 1.5);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        // COLA or not, an unmodified stft comes back exactly
+        let configs = [
+            StftConfig::new(Window::Hann, 64, 16),
+            StftConfig::new(Window::Hann, 64, 32),
+            StftConfig::new(Window::Rectangular, 64, 64),
+            StftConfig::new(Window::Blackman, 63, 10),
+            StftConfig { fft_len: 100, ..StftConfig::new(Window::Kaiser(6.0), 50, 12) },
+        ];
+        for config in configs {
+            for len in [1, 37, 1000] {
+                let input = noise(len, 1);
+                let output = istft(&stft(&input, &config));
+                assert_eq!(output.len(), len);
+                for (a, b) in output.iter().zip(input.iter()) {
+                    assert!((a - b).abs() < 1e-9, "window {} hop {} differs", config.window.len(), config.hop);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn modified_frames_test() {
+        // scaling every frame by 2 scales the output by 2
+        let input = noise(500, 1);
+        let mut stft = stft(&input, &StftConfig::new(Window::Hann, 32, 8));
+        for m in 0..stft.frames {
+            stft.frame_mut(m).iter_mut().for_each(|x| *x *= 2.0);
+        }
+        for (a, b) in istft(&stft).iter().zip(input.iter()) {
+            assert!((a - 2.0 * b).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn check_cola_test() {
+        assert!(StftConfig::<f64>::new(Window::Hann, 64, 16).check_cola().is_ok());
+        assert!(StftConfig::<f32>::new(Window::Hann, 64, 16).check_cola().is_ok());
+        assert!(StftConfig::<f64>::new(Window::Rectangular, 64, 64).check_cola().is_ok());
+        assert!(StftConfig::<f64>::new(Window::Hann, 64, 32).check_cola().is_err());
+        let err = StftConfig::<f64>::new(Window::Hann, 64, 64).check_cola().unwrap_err();
+        assert!(matches!(err, FftError::NotCola { ripple } if ripple == 1.0));
+    }
+
+    #[test]
+    fn try_istft_test() {
+        let input = noise(300, 1);
+        let output = try_istft(&stft(&input, &StftConfig::new(Window::Hann, 32, 8))).unwrap();
+        assert!(output.iter().zip(input.iter()).all(|(a, b)| (a - b).abs() < 1e-9));
+
+        // istft would give these back unmodified, but not edited frames
+        let err = try_istft(&stft(&input, &StftConfig::new(Window::Hann, 32, 16))).unwrap_err();
+        assert!(matches!(err, FftError::NotCola { .. }));
+        let err = try_istft(&stft(&input, &StftConfig::new(Window::Hann, 8, 8))).unwrap_err();
+        assert!(matches!(err, FftError::NotCola { ripple } if ripple == 1.0));
+
+        let mut truncated = stft(&input, &StftConfig::new(Window::Hann, 32, 8));
+        truncated.data.pop();
+        assert!(matches!(try_istft(&truncated), Err(FftError::MismatchedShapes { .. })));
+
+        let mut long_window = stft(&input, &StftConfig::new(Window::Hann, 32, 8));
+        long_window.config.window = crate::window(Window::Hann, 64, Symmetry::Periodic);
+        let err = try_istft(&long_window).unwrap_err();
+        assert!(matches!(err, FftError::MismatchedShapes { expected: 32, found: 64 }));
+    }
+
+    #[test]
+    fn zero_hop_test() {
+        let mut config = StftConfig::<f64>::new(Window::Hann, 32, 8);
+        config.hop = 0;
+        assert!(matches!(config.check_cola(), Err(FftError::ZeroHop)));
+        let mut stft = stft(&noise(100, 1), &StftConfig::new(Window::Hann, 32, 8));
+        stft.config.hop = 0;
+        assert!(matches!(try_istft(&stft), Err(FftError::ZeroHop)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn istft_rejects_uncovered_samples_test() {
+        // periodic Hann is zero at the start of every frame
+        istft(&stft(&noise(100, 1), &StftConfig::new(Window::Hann, 8, 8)));
+    }
+}
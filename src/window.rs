@@ -0,0 +1,326 @@
+//! window functions
+//!
+//! a signal that doesn't fit a whole number of cycles into the transform
+//! leaks into every bin. multiplying it by a window that tapers to zero at
+//! the ends trades that leakage for a wider main lobe. the coefficients are
+//! computed in f64 then rounded, like twiddle factors.
+//!
+//! every window comes in two forms. symmetric windows are for filter design.
+//! periodic windows are for spectral analysis: they are the symmetric window
+//! one sample longer with the last sample dropped, so they repeat seamlessly.
+
+use std::f64::consts::PI;
+
+use num::complex::Complex;
+
+use crate::float::FftFloat;
+
+/// which window `window` computes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+    /// all ones; the same as not windowing
+    Rectangular,
+    /// raised cosine, zero at both ends
+    Hann,
+    /// raised cosine tuned to cancel the first sidelobe
+    Hamming,
+    /// three-term cosine sum
+    Blackman,
+    /// four-term cosine sum with sidelobes below -92 dB
+    BlackmanHarris,
+    /// five-term cosine sum whose peak barely changes between bins, for
+    /// reading amplitudes accurately
+    FlatTop,
+    /// Kaiser-Bessel window with shape parameter beta; 0 is rectangular and
+    /// larger values taper more
+    Kaiser(f64),
+    /// flat top with cosine tapers over a fraction alpha of the length;
+    /// alpha 0 is rectangular and 1 is Hann
+    Tukey(f64),
+    /// Gaussian with the given standard deviation in samples
+    Gaussian(f64),
+    /// triangle, zero at both ends
+    Bartlett,
+    /// Dolph-Chebyshev window with every sidelobe the given number of dB
+    /// below the main lobe
+    Chebyshev(f64),
+}
+
+/// whether `window` computes the symmetric or the periodic form
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    /// w[n] == w[len - 1 - n]; for filter design
+    Symmetric,
+    /// one period of a window of length `len`; for spectral analysis
+    Periodic,
+}
+
+/// compute `len` coefficients of a window, peaking at 1
+///
+/// panics if a Gaussian's standard deviation isn't positive
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{Symmetry, Window};
+///
+/// let round = |w: Vec<f64>| w.iter().map(|x| (x * 1e9).round() / 1e9).collect::<Vec<_>>();
+/// assert_eq!(round(fft_rust::window(Window::Hann, 5, Symmetry::Symmetric)), vec![0., 0.5, 1., 0.5, 0.]);
+/// assert_eq!(round(fft_rust::window(Window::Hann, 4, Symmetry::Periodic)), vec![0., 0.5, 1., 0.5]);
+/// ```
+pub fn window<T: FftFloat>(window: Window, len: usize, symmetry: Symmetry) -> Vec<T> {
+    if let Window::Gaussian(sigma) = window {
+        assert!(sigma > 0.0, "a Gaussian window needs a positive standard deviation, not {}", sigma);
+    }
+    let coefficients = match symmetry {
+        Symmetry::Symmetric => symmetric(window, len),
+        Symmetry::Periodic => {
+            let mut coefficients = symmetric(window, len + 1);
+            coefficients.truncate(len);
+            coefficients
+        }
+    };
+    coefficients.into_iter().map(T::from_f64).collect()
+}
+
+/// how a window changes what a spectrum reads; see `window_stats`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowStats<T: FftFloat> {
+    /// the window's mean; a windowed sinusoid's peak is this much smaller,
+    /// so divide amplitudes by it
+    pub coherent_gain: T,
+    /// equivalent noise bandwidth in bins: the width of a rectangular filter
+    /// that lets through as much noise as one bin of the window does
+    pub enbw: T,
+    /// how many dB lower a sinusoid half way between two bins reads than
+    /// one on a bin
+    pub scalloping_loss: T,
+}
+
+/// the coherent gain, ENBW and scalloping loss of a window's coefficients;
+/// panics if `window` is empty or sums to zero
+///
+/// # Example
+///
+/// ```
+/// use fft_rust::{Symmetry, Window};
+///
+/// let hann: Vec<f64> = fft_rust::window(Window::Hann, 1024, Symmetry::Periodic);
+/// let stats = fft_rust::window_stats(&hann);
+/// assert!((stats.coherent_gain - 0.5).abs() < 1e-12);
+/// assert!((stats.enbw - 1.5).abs() < 1e-12);
+/// assert!((stats.scalloping_loss - 1.42).abs() < 0.01);
+/// ```
+pub fn window_stats<T: FftFloat>(window: &[T]) -> WindowStats<T> {
+    assert!(!window.is_empty(), "a window needs at least one coefficient");
+    let n = T::from_usize(window.len());
+    let sum: T = window.iter().copied().sum();
+    let sum_squares: T = window.iter().map(|w| *w * *w).sum();
+    assert!(sum != T::zero(), "the window's coefficients sum to zero");
+
+    // the response to a sinusoid half a bin above DC
+    let half_bin: Complex<T> = window.iter().enumerate().map(|(k, w)| {
+        let phase = T::from_f64(-PI * k as f64) / n;
+        Complex::new(phase.cos(), phase.sin()) * *w
+    }).sum();
+
+    WindowStats {
+        coherent_gain: sum / n,
+        enbw: n * sum_squares / (sum * sum),
+        scalloping_loss: -T::from_f64(20.0) * (half_bin.norm() / sum.abs()).log10(),
+    }
+}
+
+/// the symmetric form of `window`, in f64
+fn symmetric(window: Window, len: usize) -> Vec<f64> {
+    if len <= 1 {
+        return vec![1.0; len];
+    }
+    let m = (len - 1) as f64;
+    match window {
+        Window::Rectangular => vec![1.0; len],
+        Window::Hann => cosine_sum(&[0.5, 0.5], len),
+        Window::Hamming => cosine_sum(&[0.54, 0.46], len),
+        Window::Blackman => cosine_sum(&[0.42, 0.5, 0.08], len),
+        Window::BlackmanHarris => cosine_sum(&[0.35875, 0.48829, 0.14128, 0.01168], len),
+        Window::FlatTop => cosine_sum(
+            &[0.21557895, 0.41663158, 0.277263158, 0.083578947, 0.006947368], len
+        ),
+        Window::Kaiser(beta) => (0..len).map(|n| {
+            let x = 2.0 * n as f64 / m - 1.0;
+            bessel_i0(beta * (1.0 - x * x).max(0.0).sqrt()) / bessel_i0(beta)
+        }).collect(),
+        Window::Tukey(alpha) => {
+            if alpha <= 0.0 {
+                return vec![1.0; len];
+            }
+            let alpha = alpha.min(1.0);
+            (0..len).map(|n| {
+                // distance from the nearer end, as a fraction of the length
+                let x = (n as f64 / m).min(1.0 - n as f64 / m);
+                if x < alpha / 2.0 { 0.5 - 0.5 * (2.0 * PI * x / alpha).cos() } else { 1.0 }
+            }).collect()
+        }
+        Window::Gaussian(sigma) => (0..len).map(|n| {
+            let x = (n as f64 - m / 2.0) / sigma;
+            (-0.5 * x * x).exp()
+        }).collect(),
+        Window::Bartlett => (0..len).map(|n| 1.0 - (2.0 * n as f64 / m - 1.0).abs()).collect(),
+        Window::Chebyshev(attenuation) => chebyshev(attenuation, len),
+    }
+}
+
+/// a0 - a1 cos(2 pi n / m) + a2 cos(4 pi n / m) - ...
+fn cosine_sum(coefficients: &[f64], len: usize) -> Vec<f64> {
+    let m = (len - 1) as f64;
+    (0..len).map(|n| {
+        coefficients.iter().enumerate().map(|(k, a)| {
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            sign * a * (2.0 * PI * (k * n) as f64 / m).cos()
+        }).sum()
+    }).collect()
+}
+
+/// the modified Bessel function of the first kind, order zero, from its
+/// power series
+pub(crate) fn bessel_i0(x: f64) -> f64 {
+    let quarter_x2 = x * x / 4.0;
+    let (mut sum, mut term, mut k) = (1.0, 1.0, 1.0);
+    while term > sum * f64::EPSILON {
+        term *= quarter_x2 / (k * k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+/// the Dolph-Chebyshev window: samples of a Chebyshev polynomial, which
+/// ripples evenly at the sidelobe level, taken back to the time domain
+fn chebyshev(attenuation: f64, len: usize) -> Vec<f64> {
+    let order = (len - 1) as f64;
+    let beta = ((10f64.powf(attenuation.abs() / 20.0)).acosh() / order).cosh();
+    let odd = len % 2 == 1;
+    let response: Vec<Complex<f64>> = (0..len).map(|k| {
+        let x = beta * (PI * k as f64 / len as f64).cos();
+        let p = if x > 1.0 {
+            (order * x.acosh()).cosh()
+        } else if x < -1.0 {
+            let sign = if odd { 1.0 } else { -1.0 };
+            sign * (order * (-x).acosh()).cosh()
+        } else {
+            (order * x.acos()).cos()
+        };
+        if odd {
+            Complex::new(p, 0.0)
+        } else {
+            // shift by half a sample so the even-length window is centred
+            Complex::new(0.0, PI * k as f64 / len as f64).exp() * p
+        }
+    }).collect();
+    let taps: Vec<f64> = crate::fft_exact(&response).iter().map(|x| x.re).collect();
+
+    // the transform holds the right half of the window, starting at the middle
+    let half = len / 2 + 1;
+    let mut coefficients: Vec<f64> = taps[1..half].iter().rev().copied().collect();
+    if odd {
+        coefficients.extend_from_slice(&taps[..half]);
+    } else {
+        coefficients.extend_from_slice(&taps[1..half]);
+    }
+    let peak = coefficients.iter().copied().fold(f64::MIN, f64::max);
+    coefficients.iter().map(|w| w / peak).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Window; 11] = [
+        Window::Rectangular, Window::Hann, Window::Hamming, Window::Blackman,
+        Window::BlackmanHarris, Window::FlatTop, Window::Kaiser(8.6), Window::Tukey(0.5),
+        Window::Gaussian(3.0), Window::Bartlett, Window::Chebyshev(80.0),
+    ];
+
+    #[test]
+    fn symmetric_test() {
+        for kind in ALL {
+            for len in [0, 1, 2, 15, 16] {
+                let w: Vec<f64> = window(kind, len, Symmetry::Symmetric);
+                assert_eq!(w.len(), len);
+                for n in 0..len {
+                    assert!((w[n] - w[len - 1 - n]).abs() < 1e-12, "{:?} isn't symmetric", kind);
+                }
+                // the flat-top coefficients are rounded to 9 places
+                if len % 2 == 1 {
+                    assert!((w[len / 2] - 1.0).abs() < 1e-8, "{:?} doesn't peak at 1", kind);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn periodic_test() {
+        for kind in ALL {
+            let periodic: Vec<f64> = window(kind, 16, Symmetry::Periodic);
+            let symmetric: Vec<f64> = window(kind, 17, Symmetry::Symmetric);
+            assert_eq!(periodic[..], symmetric[..16]);
+        }
+    }
+
+    #[test]
+    fn known_values_test() {
+        let close = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < 1e-9);
+        assert!(close(&window(Window::Hamming, 3, Symmetry::Symmetric), &[0.08, 1., 0.08]));
+        assert!(close(&window(Window::Blackman, 3, Symmetry::Symmetric), &[0., 1., 0.]));
+        assert!(close(&window(Window::Bartlett, 5, Symmetry::Symmetric), &[0., 0.5, 1., 0.5, 0.]));
+        assert!(close(&window(Window::Tukey(1.0), 5, Symmetry::Symmetric), &[0., 0.5, 1., 0.5, 0.]));
+        assert!(close(&window(Window::Tukey(0.0), 3, Symmetry::Symmetric), &[1., 1., 1.]));
+        assert!(close(&window(Window::Kaiser(0.0), 3, Symmetry::Symmetric), &[1., 1., 1.]));
+        assert!((bessel_i0(1.0) - 1.2660658777520082).abs() < 1e-15);
+        let kaiser: Vec<f64> = window(Window::Kaiser(5.0), 9, Symmetry::Symmetric);
+        assert!((kaiser[0] - 1.0 / bessel_i0(5.0)).abs() < 1e-15);
+        let gaussian: Vec<f64> = window(Window::Gaussian(2.0), 5, Symmetry::Symmetric);
+        assert!((gaussian[0] - (-0.5f64).exp()).abs() < 1e-15);
+    }
+
+    #[test]
+    fn chebyshev_sidelobes_test() {
+        // every sidelobe of the zero-padded response sits at the attenuation
+        for len in [31, 32] {
+            let w: Vec<f64> = window(Window::Chebyshev(60.0), len, Symmetry::Symmetric);
+            let mut padded: Vec<_> = w.iter().map(|w| Complex::new(*w, 0.0)).collect();
+            padded.resize(4096, Complex::new(0.0, 0.0));
+            let response: Vec<f64> = crate::fft_exact(&padded)[..2048].iter().map(|x| x.norm()).collect();
+            let main_lobe_end = (1..2048).find(|&k| response[k] > response[k - 1]).unwrap();
+            let sidelobe = response[main_lobe_end..].iter().copied().fold(0.0, f64::max);
+            let db = 20.0 * (sidelobe / response[0]).log10();
+            assert!((db + 60.0).abs() < 0.1, "length {} sidelobes at {} dB", len, db);
+        }
+    }
+
+    #[test]
+    fn window_stats_test() {
+        // (window, coherent gain, ENBW in bins, scalloping loss in dB), from
+        // Harris, "On the use of windows for harmonic analysis with the DFT"
+        let expected = [
+            (Window::Rectangular, 1.0, 1.0, 3.92),
+            (Window::Hann, 0.5, 1.5, 1.42),
+            (Window::Hamming, 0.54, 1.36, 1.75),
+            (Window::BlackmanHarris, 0.36, 2.0, 0.83),
+            (Window::FlatTop, 0.22, 3.77, 0.01),
+        ];
+        for (kind, coherent_gain, enbw, scalloping_loss) in expected {
+            let w: Vec<f64> = window(kind, 4096, Symmetry::Periodic);
+            let stats = window_stats(&w);
+            assert!((stats.coherent_gain - coherent_gain).abs() < 0.005, "{:?}", kind);
+            assert!((stats.enbw - enbw).abs() < 0.005, "{:?}", kind);
+            assert!((stats.scalloping_loss - scalloping_loss).abs() < 0.01, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn gaussian_rejects_zero_sigma_test() {
+        window::<f64>(Window::Gaussian(0.0), 8, Symmetry::Symmetric);
+    }
+}